@@ -1,9 +1,7 @@
-use crossterm::event::{Event, KeyCode, KeyEvent};
+use crossterm::event::{Event, KeyCode};
 use tdrop::{
-    backend::{Backend, CrosstermBackend},
     component::confirmation::{Confirmation, ConfirmationState},
-    terminal::Terminal,
-    DefaultTerminal,
+    terminal::{Control, Outcome},
 };
 
 fn main() {
@@ -12,47 +10,31 @@ fn main() {
     let res: std::io::Result<()> = tdrop::run(|term| {
         // Here you can do whatever procedural things you like!
 
-        // But if you want to render a stateful widget, enter a loop.
-        // Poll events from the terminal to get both any events from the chosen terminal backend
-        // and also whether or not an exit signal (CTRL+C) was sent.
+        // But if you want to render a stateful widget, run it until the event handler says it's
+        // done. The terminal takes care of polling events, redrawing at the given framerate and
+        // responding to resizes.
         let mut confirmation_state = ConfirmationState { done: false };
-        loop {
-            let confirmation = Confirmation {};
-            term.draw(|frame| {
-                frame.render_stateful_component(
-                    confirmation,
-                    frame.area(),
-                    &mut confirmation_state,
-                );
-            });
-
-            if confirmation_state.done {
-                break;
-            }
-            if let Some((ev, should_exit)) = term.poll_event() {
-                if should_exit {
-                    exit(term);
-                    // This break statement is inserted because `rust-analyzer` doesn't recognise
-                    // std::process:exit() as exiting an endless loop
-                    break;
-                }
-                match ev {
-                    Event::Key(k) => match k.code {
-                        KeyCode::Char('q') => {
-                            confirmation_state.done = true;
-                            continue;
-                        }
-                        _ => {}
-                    },
-                    _ => {}
+        let outcome = term.run_stateful(
+            || Confirmation {},
+            &mut confirmation_state,
+            term.frame_rate(),
+            |state, event| match event {
+                Some(Event::Key(k)) if k.code == KeyCode::Char('q') => {
+                    state.done = true;
+                    Control::Exit
                 }
-            }
+                _ => Control::Continue,
+            },
+        )?;
+
+        // An exit signal (CTRL+C) was sent
+        if outcome == Outcome::Interrupted {
+            println!("Exiting now!");
         }
         Ok(())
     });
-}
 
-fn exit(term: &mut DefaultTerminal) {
-    println!("Exiting now!");
-    std::process::exit(0)
+    if let Err(err) = res {
+        eprintln!("{err}");
+    }
 }
@@ -1,4 +1,5 @@
 use std::io::{self, Write};
+use std::ops::Range;
 use std::time::Duration;
 
 use crate::buffer::Cell;
@@ -9,14 +10,18 @@ use crossterm::cursor::{Hide, MoveTo, Show};
 
 use crossterm::event::KeyModifiers;
 use crossterm::style::{
-    Attribute as CrosstermAttr, Attributes as CrosstermAttrs, Color as CrosstermColor,
-    Colors as CrosstermColors, ContentStyle, Print, SetAttribute, SetBackgroundColor, SetColors,
-    SetForegroundColor,
+    Attribute as CrosstermAttr, Color as CrosstermColor, Colors as CrosstermColors, Print,
+    SetAttribute, SetColors,
 };
 
-use crossterm::terminal::{self, Clear};
+use crossterm::terminal::Clear;
 use crossterm::{execute, queue};
 
+#[cfg(test)]
+mod test;
+#[cfg(test)]
+pub(crate) use test::TestBackend;
+
 pub enum ClearType {
     All,
     AfterCursor,
@@ -47,17 +52,81 @@ pub trait Backend {
 
     fn clear(&mut self) -> Result<(), Self::Error>;
 
+    /// Limit scrolling to the given rows, so that line feeds on the last of them only scroll
+    /// those rows. The cursor position is undefined afterwards.
+    fn set_scroll_region(&mut self, rows: Range<u16>) -> Result<(), Self::Error>;
+
+    /// Let the whole screen scroll again. See [set_scroll_region](Self::set_scroll_region).
+    fn reset_scroll_region(&mut self) -> Result<(), Self::Error>;
+
     fn size(&self) -> Result<Size, Self::Error>;
 
     fn flush(&mut self) -> Result<(), Self::Error>;
 
+    /// Put the terminal into raw mode, where input is read without line buffering or echo and
+    /// output isn't processed.
+    fn enable_raw_mode(&mut self) -> Result<(), Self::Error>;
+
+    /// Return the terminal to normal mode. See [enable_raw_mode](Self::enable_raw_mode).
+    fn disable_raw_mode(&mut self) -> Result<(), Self::Error>;
+
+    /// Whether the terminal is in raw mode, which may have been left without going through this
+    /// backend (e.g. by [restore](crate::restore)).
+    fn is_raw_mode_enabled(&self) -> Result<bool, Self::Error>;
+
+    /// Write a string to the terminal as-is. Intended for escape sequences which aren't otherwise
+    /// covered by the backend.
+    fn write_raw(&mut self, content: &str) -> Result<(), Self::Error>;
+
     /// Retrieve any events from the terminal backend, intercepting CTRL+C if the terminal is
     /// configured to
-    fn read_event(&self) -> Option<(Self::Event, bool)>;
+    fn read_event(&self) -> Option<(Self::Event, bool)> {
+        self.read_event_timeout(Duration::ZERO)
+    }
+
+    /// Wait up to `timeout` for an event from the terminal backend. See
+    /// [read_event](Self::read_event).
+    fn read_event_timeout(&self, timeout: Duration) -> Option<(Self::Event, bool)>;
+
+    /// The new size of the terminal if the event is a resize.
+    fn resize_event(&self, _event: &Self::Event) -> Option<Size> {
+        None
+    }
+
+    /// Whether the event is a request to suspend the process, which is CTRL+Z on most
+    /// terminals.
+    fn suspend_event(&self, _event: &Self::Event) -> bool {
+        false
+    }
+
+    /// Stop the process until it is continued, like the shell does on CTRL+Z. Backends which
+    /// don't control a process do nothing.
+    fn suspend_process(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Write a query to the terminal and wait up to `timeout` for its reply.
+    ///
+    /// The query is followed by a primary device attributes request (DA1), which practically
+    /// every terminal answers. Everything received up to and including that answer is returned,
+    /// so queries the terminal doesn't understand don't cost the full timeout. Returns [None] if
+    /// the terminal did not answer in time or replies cannot be read.
+    fn query(&mut self, query: &str, timeout: Duration) -> Result<Option<String>, Self::Error>;
+
+    /// The total number of bytes written to the terminal so far, or 0 if the backend doesn't
+    /// count them.
+    fn bytes_written(&self) -> u64 {
+        0
+    }
 }
 
+/// The primary device attributes request, used to fence queries.
+const PRIMARY_DEVICE_ATTRIBUTES: &str = "\x1b[c";
+
 pub struct CrosstermBackend<W: Write> {
     writer: W,
+    /// The number of bytes written through the backend so far
+    bytes_written: u64,
 }
 
 impl<W> CrosstermBackend<W>
@@ -65,7 +134,10 @@ where
     W: Write,
 {
     pub const fn new(writer: W) -> Self {
-        Self { writer }
+        Self {
+            writer,
+            bytes_written: 0,
+        }
     }
 
     pub const fn writer(&self) -> &W {
@@ -82,7 +154,9 @@ where
     W: Write,
 {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.writer.write(buf)
+        let written = self.writer.write(buf)?;
+        self.bytes_written += written as u64;
+        Ok(written)
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
@@ -101,53 +175,45 @@ where
     where
         I: Iterator<Item = (u16, u16, &'a Cell)>,
     {
-        let mut fg = Color::Reset;
-        let mut bg = Color::Reset;
-        let mut attrs = Attributes::none();
+        let mut sgr = Sgr::default();
         let mut last_pos: Option<Position> = None;
         for (x, y, cell) in content {
             if !matches!(last_pos, Some(p) if x == p.x + 1 && y == p.y) {
-                queue!(self.writer, MoveTo(x, y))?;
-            }
-            last_pos = Some(Position { x, y });
-            if cell.attributes != attrs {
-                let diff = AttributeDiff {
-                    from: attrs,
-                    to: cell.attributes,
-                };
-                diff.queue(&mut self.writer)?;
-                attrs = cell.attributes;
+                queue!(self, MoveTo(x, y))?;
             }
-            if (cell.fg != fg || cell.bg != bg) {
-                queue!(
-                    self.writer,
-                    SetColors(CrosstermColors::new(
-                        cell.fg.into_crossterm(),
-                        cell.bg.into_crossterm()
-                    ))
-                )?;
+            // Wide symbols advance the cursor past the cells they cover
+            let width = u16::try_from(cell.width()).unwrap_or(1).max(1);
+            last_pos = Some(Position {
+                x: x.saturating_add(width - 1),
+                y,
+            });
+            sgr.queue(&mut *self, cell)?;
+            queue!(self, Print(cell.symbol()))?;
+
+            // Escape sequences such as inline images leave the cursor in an unknown position
+            if cell.symbol().starts_with('\x1b') {
+                last_pos = None;
             }
-
-            queue!(self.writer, Print(cell.symbol()))?;
         }
 
-        Ok(())
+        // The next draw, or anything else written, starts from the default style
+        sgr.reset(&mut *self)
     }
 
     fn append_lines(&mut self, n: u16) -> io::Result<()> {
         for _ in 0..n {
-            queue!(self.writer, Print("\n"))?;
+            queue!(self, Print("\n"))?;
         }
 
         self.writer.flush()
     }
 
     fn hide_cursor(&mut self) -> io::Result<()> {
-        execute!(self.writer, Hide)
+        execute!(self, Hide)
     }
 
     fn show_cursor(&mut self) -> io::Result<()> {
-        execute!(self.writer, Show)
+        execute!(self, Show)
     }
 
     fn get_cursor_position(&mut self) -> io::Result<Position> {
@@ -158,7 +224,7 @@ where
 
     fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
         let Position { x, y } = position.into();
-        execute!(self.writer, MoveTo(x, y))
+        execute!(self, MoveTo(x, y))
     }
 
     fn clear(&mut self) -> io::Result<()> {
@@ -167,7 +233,7 @@ where
 
     fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
         execute!(
-            self.writer,
+            self,
             Clear(match clear_type {
                 ClearType::All => crossterm::terminal::ClearType::All,
                 ClearType::AfterCursor => crossterm::terminal::ClearType::FromCursorDown,
@@ -178,6 +244,18 @@ where
         )
     }
 
+    fn set_scroll_region(&mut self, rows: Range<u16>) -> io::Result<()> {
+        // DECSTBM takes the one-based first and last rows
+        queue!(
+            self,
+            Print(format!("\x1b[{};{}r", rows.start + 1, rows.end))
+        )
+    }
+
+    fn reset_scroll_region(&mut self) -> io::Result<()> {
+        queue!(self, Print("\x1b[r"))
+    }
+
     fn size(&self) -> io::Result<Size> {
         let (width, height) = crossterm::terminal::size()?;
 
@@ -188,27 +266,183 @@ where
         self.writer.flush()
     }
 
-    fn read_event(&self) -> Option<(Self::Event, bool)> {
-        if crossterm::event::poll(Duration::from_secs(0)).ok()? {
-            let ev = crossterm::event::read().ok()?;
-            let mut should_exit = false;
+    fn enable_raw_mode(&mut self) -> io::Result<()> {
+        crossterm::terminal::enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&mut self) -> io::Result<()> {
+        crossterm::terminal::disable_raw_mode()
+    }
+
+    fn is_raw_mode_enabled(&self) -> io::Result<bool> {
+        crossterm::terminal::is_raw_mode_enabled()
+    }
+
+    fn write_raw(&mut self, content: &str) -> io::Result<()> {
+        queue!(self, Print(content))
+    }
 
-            match ev {
+    fn read_event_timeout(&self, timeout: Duration) -> Option<(Self::Event, bool)> {
+        if crossterm::event::poll(timeout).ok()? {
+            let ev = crossterm::event::read().ok()?;
+            let should_exit = matches!(
+                ev,
                 crossterm::event::Event::Key(crossterm::event::KeyEvent {
                     code: crossterm::event::KeyCode::Char('c'),
                     modifiers: KeyModifiers::CONTROL,
                     ..
-                }) => {
-                    should_exit = true;
-                }
-                _ => {}
-            }
+                })
+            );
 
             return Some((ev, should_exit));
         }
 
         None
     }
+
+    fn resize_event(&self, event: &Self::Event) -> Option<Size> {
+        match event {
+            crossterm::event::Event::Resize(width, height) => Some(Size {
+                width: *width,
+                height: *height,
+            }),
+            _ => None,
+        }
+    }
+
+    fn suspend_event(&self, event: &Self::Event) -> bool {
+        matches!(
+            event,
+            crossterm::event::Event::Key(crossterm::event::KeyEvent {
+                code: crossterm::event::KeyCode::Char('z'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            })
+        )
+    }
+
+    #[cfg(unix)]
+    fn suspend_process(&mut self) -> io::Result<()> {
+        // SAFETY: raising a signal has no preconditions. SIGTSTP stops the process and the call
+        // returns once it is continued.
+        if unsafe { libc::raise(libc::SIGTSTP) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn query(&mut self, query: &str, timeout: Duration) -> io::Result<Option<String>> {
+        queue!(self, Print(query), Print(PRIMARY_DEVICE_ATTRIBUTES))?;
+        self.writer.flush()?;
+
+        read_reply(timeout)
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+}
+
+/// Read from the controlling terminal until a primary device attributes reply arrives or the
+/// timeout expires.
+#[cfg(unix)]
+fn read_reply(timeout: Duration) -> io::Result<Option<String>> {
+    use std::io::Read;
+    use std::os::fd::AsRawFd;
+    use std::time::Instant;
+
+    let mut tty = std::fs::File::open("/dev/tty")?;
+    let deadline = Instant::now() + timeout;
+    let mut reply = Vec::new();
+    let mut chunk = [0; 256];
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(None);
+        }
+
+        // A signal such as SIGWINCH interrupts the wait, which is retried with the time left
+        match wait_for_input(tty.as_raw_fd(), remaining) {
+            Ok(true) => {}
+            Ok(false) => return Ok(None),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+
+        let read = match tty.read(&mut chunk) {
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        if read == 0 {
+            return Ok(None);
+        }
+        reply.extend_from_slice(&chunk[..read]);
+
+        if has_device_attributes(&reply) {
+            return Ok(Some(String::from_utf8_lossy(&reply).into_owned()));
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn read_reply(_timeout: Duration) -> io::Result<Option<String>> {
+    Ok(None)
+}
+
+/// Wait until the file descriptor is readable, returning false on timeout.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn wait_for_input(fd: libc::c_int, timeout: Duration) -> io::Result<bool> {
+    let mut poll_fd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let timeout = libc::c_int::try_from(timeout.as_millis()).unwrap_or(libc::c_int::MAX);
+
+    // SAFETY: `poll_fd` is a valid pollfd and the count matches the single entry passed.
+    match unsafe { libc::poll(&mut poll_fd, 1, timeout) } {
+        -1 => Err(io::Error::last_os_error()),
+        ready => Ok(ready > 0),
+    }
+}
+
+// macOS doesn't support `poll` on ttys, so `select` is used there instead.
+#[cfg(target_os = "macos")]
+fn wait_for_input(fd: libc::c_int, timeout: Duration) -> io::Result<bool> {
+    let mut timeout = libc::timeval {
+        tv_sec: timeout.as_secs() as libc::time_t,
+        tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+    };
+
+    // SAFETY: the fd_set is zero-initialised by FD_ZERO before use and only contains `fd`.
+    unsafe {
+        let mut set: libc::fd_set = std::mem::zeroed();
+        libc::FD_ZERO(&mut set);
+        libc::FD_SET(fd, &mut set);
+        match libc::select(
+            fd + 1,
+            &mut set,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut timeout,
+        ) {
+            -1 => Err(io::Error::last_os_error()),
+            ready => Ok(ready > 0),
+        }
+    }
+}
+
+/// Whether the bytes contain a primary device attributes reply (`CSI ? Ps ; ... c`).
+fn has_device_attributes(bytes: &[u8]) -> bool {
+    bytes.windows(3).enumerate().any(|(i, window)| {
+        window == b"\x1b[?"
+            && bytes[i + 3..]
+                .iter()
+                .find(|b| !(b.is_ascii_digit() || **b == b';'))
+                .is_some_and(|b| *b == b'c')
+    })
 }
 
 pub trait IntoCrossterm<C> {
@@ -271,6 +505,50 @@ impl FromCrossterm<CrosstermColor> for Color {
     }
 }
 
+/// The colors and attributes last set on the terminal, so that only changes between cells are
+/// written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Sgr {
+    fg: Color,
+    bg: Color,
+    attributes: Attributes,
+}
+
+impl Sgr {
+    /// Write whatever is needed to switch to the colors and attributes of the cell.
+    pub(crate) fn queue<W: Write>(&mut self, mut w: W, cell: &Cell) -> io::Result<()> {
+        if cell.attributes != self.attributes {
+            let diff = AttributeDiff {
+                from: self.attributes,
+                to: cell.attributes,
+            };
+            diff.queue(&mut w)?;
+            self.attributes = cell.attributes;
+        }
+        if cell.fg != self.fg || cell.bg != self.bg {
+            queue!(
+                w,
+                SetColors(CrosstermColors::new(
+                    cell.fg.into_crossterm(),
+                    cell.bg.into_crossterm()
+                ))
+            )?;
+            self.fg = cell.fg;
+            self.bg = cell.bg;
+        }
+        Ok(())
+    }
+
+    /// Reset the colors and attributes if any are set.
+    pub(crate) fn reset<W: Write>(&mut self, mut w: W) -> io::Result<()> {
+        if *self != Self::default() {
+            queue!(w, SetAttribute(CrosstermAttr::Reset))?;
+            *self = Self::default();
+        }
+        Ok(())
+    }
+}
+
 struct AttributeDiff {
     pub from: Attributes,
     pub to: Attributes,
@@ -335,3 +613,36 @@ impl AttributeDiff {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Backend, CrosstermBackend};
+    use crate::{buffer::Cell, style::Color};
+
+    #[test]
+    fn counts_bytes_written() {
+        let mut backend = CrosstermBackend::new(Vec::new());
+        let mut cell = Cell::EMPTY;
+        cell.set_symbol("x");
+        backend.draw([(0, 0, &cell)].into_iter()).unwrap();
+        backend.write_raw("\x1b[0m").unwrap();
+        assert!(backend.bytes_written() > 0);
+        assert_eq!(backend.bytes_written(), backend.writer().len() as u64);
+    }
+
+    #[test]
+    fn resets_style_after_draw() {
+        let mut backend = CrosstermBackend::new(Vec::new());
+        let mut red = Cell::EMPTY;
+        red.set_symbol("e").fg = Color::Red;
+        backend.draw([(0, 0, &red)].into_iter()).unwrap();
+        assert!(backend.writer().ends_with(b"e\x1b[0m"));
+
+        // A default cell drawn afterwards needs no style of its own
+        let start = backend.writer().len();
+        let mut plain = Cell::EMPTY;
+        plain.set_symbol("x");
+        backend.draw([(0, 1, &plain)].into_iter()).unwrap();
+        assert_eq!(&backend.writer()[start..], b"\x1b[2;1Hx");
+    }
+}
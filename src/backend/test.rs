@@ -0,0 +1,401 @@
+//! A [Backend] which renders to an in-memory screen, for testing.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io;
+use std::ops::Range;
+use std::time::Duration;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+use crate::backend::{Backend, ClearType};
+use crate::buffer::{Buffer, Cell};
+use crate::layout::{Position, Rect, Size};
+
+/// A [Backend] which renders to an in-memory screen with scrollback instead of a terminal.
+///
+/// Lines scrolled off the top of the screen are kept in the scrollback, which makes it possible
+/// to test output which is meant to stay behind in the terminal history. Resizing the screen
+/// rewraps lines like a terminal emulator does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestBackend {
+    screen: Buffer,
+    scrollback: Vec<Vec<Cell>>,
+    /// Whether each row on screen was wrapped onto the next one by a resize
+    wrapped: Vec<bool>,
+    cursor: Position,
+    scroll_region: Option<Range<u16>>,
+    cursor_visible: bool,
+    raw_mode: bool,
+    events: RefCell<VecDeque<Event>>,
+}
+
+impl TestBackend {
+    /// Create a new [TestBackend] with an empty screen of the given size.
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            screen: Buffer::empty(Rect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            }),
+            scrollback: vec![],
+            wrapped: vec![false; usize::from(height)],
+            cursor: Position::default(),
+            scroll_region: None,
+            cursor_visible: true,
+            raw_mode: false,
+            events: RefCell::default(),
+        }
+    }
+
+    /// The cells currently on screen.
+    pub const fn screen(&self) -> &Buffer {
+        &self.screen
+    }
+
+    /// The lines on screen as strings, with trailing whitespace removed.
+    pub fn screen_lines(&self) -> Vec<String> {
+        self.screen
+            .content
+            .chunks(usize::from(self.screen.area.width.max(1)))
+            .map(line)
+            .collect()
+    }
+
+    /// The lines which were scrolled off the screen, oldest first, with trailing whitespace
+    /// removed.
+    pub fn scrollback_lines(&self) -> Vec<String> {
+        self.scrollback.iter().map(|row| line(row)).collect()
+    }
+
+    /// Whether the cursor is visible.
+    pub const fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    /// Whether the terminal is in raw mode.
+    pub const fn raw_mode(&self) -> bool {
+        self.raw_mode
+    }
+
+    /// The position of the cursor.
+    pub const fn cursor(&self) -> Position {
+        self.cursor
+    }
+
+    /// Queue an event to be returned by [read_event](Backend::read_event).
+    pub fn push_event(&mut self, event: Event) {
+        self.events.get_mut().push_back(event);
+    }
+
+    /// Resize the screen, rewrapping lines on screen to the new width and moving the cursor along
+    /// with them.
+    ///
+    /// If the lines no longer fit, blank rows below the cursor are dropped before rows at the top
+    /// are scrolled into the scrollback.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        let old_width = usize::from(self.screen.area.width.max(1));
+        let new_width = usize::from(width.max(1));
+
+        // Join rows which were wrapped back into lines, keeping track of the cursor within them
+        let mut lines: Vec<Vec<Cell>> = vec![];
+        let mut cursor = (0, 0);
+        let mut continued = false;
+        for (y, row) in self.screen.content.chunks(old_width).enumerate() {
+            if !continued {
+                lines.push(vec![]);
+            }
+            let index = lines.len() - 1;
+            let line = &mut lines[index];
+            if y == usize::from(self.cursor.y) {
+                cursor = (index, line.len() + usize::from(self.cursor.x));
+            }
+            line.extend_from_slice(row);
+            continued = self.wrapped[y];
+        }
+
+        let mut rows = vec![];
+        let mut wrapped = vec![];
+        let mut cursor_position = Position::default();
+        for (i, mut line) in lines.into_iter().enumerate() {
+            let used = line
+                .iter()
+                .rposition(|cell| *cell != Cell::EMPTY)
+                .map_or(0, |last| last + 1);
+            line.truncate(used);
+
+            let first_row = rows.len();
+            let chunks: Vec<&[Cell]> = line.chunks(new_width).collect();
+            for (j, chunk) in chunks.iter().enumerate() {
+                let mut row = chunk.to_vec();
+                row.resize(new_width, Cell::EMPTY);
+                rows.push(row);
+                wrapped.push(j + 1 < chunks.len());
+            }
+            if chunks.is_empty() {
+                rows.push(vec![Cell::EMPTY; new_width]);
+                wrapped.push(false);
+            }
+
+            if i == cursor.0 {
+                let offset = cursor.1.min(line.len());
+                cursor_position = Position {
+                    x: (offset % new_width) as u16,
+                    y: (first_row + offset / new_width) as u16,
+                };
+            }
+        }
+
+        // Drop blank rows below the cursor, then scroll rows off the top
+        let height_rows = usize::from(height);
+        while rows.len() > height_rows
+            && rows.len() > usize::from(cursor_position.y) + 1
+            && rows
+                .last()
+                .is_some_and(|row| row.iter().all(|c| *c == Cell::EMPTY))
+        {
+            rows.pop();
+            wrapped.pop();
+        }
+        while rows.len() > height_rows {
+            self.scrollback.push(rows.remove(0));
+            wrapped.remove(0);
+            cursor_position.y = cursor_position.y.saturating_sub(1);
+        }
+        rows.resize(height_rows, vec![Cell::EMPTY; new_width]);
+        wrapped.resize(height_rows, false);
+
+        self.screen = Buffer::empty(Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        });
+        self.screen.content = rows.concat();
+        self.wrapped = wrapped;
+        self.cursor = cursor_position;
+    }
+
+    /// Move the cursor down a line, scrolling the screen or scroll region if it is on the last
+    /// line.
+    fn line_feed(&mut self) {
+        let Size { width, height } = self.screen.area.as_size();
+        let region = self.scroll_region.clone().unwrap_or(0..height);
+        if self.cursor.y + 1 != region.end {
+            self.cursor.y = (self.cursor.y + 1).min(height.saturating_sub(1));
+            return;
+        }
+
+        // Like most terminals, rows scrolled off a region at the top of the screen are kept
+        let width = usize::from(width);
+        let start = usize::from(region.start) * width;
+        let end = usize::from(region.end) * width;
+        let top: Vec<Cell> = self.screen.content.drain(start..start + width).collect();
+        if region.start == 0 {
+            self.scrollback.push(top);
+        }
+        self.screen
+            .content
+            .splice(end - width..end - width, vec![Cell::EMPTY; width]);
+        self.wrapped.remove(usize::from(region.start));
+        self.wrapped.insert(usize::from(region.end) - 1, false);
+    }
+
+    fn clear_cells(&mut self, from: Position, to: Position) {
+        let start = self.screen.index_of(from.x, from.y);
+        let end = self.screen.index_of(to.x, to.y);
+        for cell in &mut self.screen.content[start..=end] {
+            cell.reset();
+        }
+
+        // Erasing the end of a row also removes its continuation onto the next one
+        let last_column = self.screen.area.width.saturating_sub(1);
+        for y in from.y..=to.y {
+            if y < to.y || to.x == last_column {
+                self.wrapped[usize::from(y)] = false;
+            }
+        }
+    }
+}
+
+fn line(cells: &[Cell]) -> String {
+    let mut line = String::new();
+    let mut to_skip = 0;
+    for cell in cells {
+        if to_skip == 0 && !cell.skip {
+            line.push_str(cell.symbol());
+            to_skip = cell.width().saturating_sub(1);
+        } else {
+            to_skip = to_skip.saturating_sub(1);
+        }
+    }
+
+    line.trim_end().to_string()
+}
+
+impl Backend for TestBackend {
+    type Error = io::Error;
+    type Event = Event;
+
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        for (x, y, cell) in content {
+            let Size { width, height } = self.screen.area.as_size();
+            if x < width && y < height {
+                self.screen[(x, y)] = cell.clone();
+            }
+            // Like a terminal, the cursor stays on the last column rather than wrapping
+            let advance = u16::try_from(cell.width()).unwrap_or(u16::MAX);
+            self.cursor = Position {
+                x: x.saturating_add(advance).min(width.saturating_sub(1)),
+                y,
+            };
+        }
+
+        Ok(())
+    }
+
+    fn append_lines(&mut self, n: u16) -> io::Result<()> {
+        for _ in 0..n {
+            self.line_feed();
+        }
+
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.cursor_visible = false;
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.cursor_visible = true;
+        Ok(())
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        Ok(self.cursor)
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        let position = position.into();
+        self.cursor = Position {
+            x: position.x.min(self.screen.area.width.saturating_sub(1)),
+            y: position.y.min(self.screen.area.height.saturating_sub(1)),
+        };
+        Ok(())
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        let Size { width, height } = self.screen.area.as_size();
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        let start = Position { x: 0, y: 0 };
+        let end = Position {
+            x: width - 1,
+            y: height - 1,
+        };
+        let line_start = Position {
+            x: 0,
+            y: self.cursor.y,
+        };
+        let line_end = Position {
+            x: width - 1,
+            y: self.cursor.y,
+        };
+
+        match clear_type {
+            ClearType::All => self.clear_cells(start, end),
+            ClearType::AfterCursor => self.clear_cells(self.cursor, end),
+            ClearType::BeforeCursor => self.clear_cells(start, self.cursor),
+            ClearType::CurrentLine => self.clear_cells(line_start, line_end),
+            ClearType::UntilNewLine => self.clear_cells(self.cursor, line_end),
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.clear_region(ClearType::All)
+    }
+
+    fn set_scroll_region(&mut self, rows: Range<u16>) -> io::Result<()> {
+        self.scroll_region = Some(rows);
+        Ok(())
+    }
+
+    fn reset_scroll_region(&mut self) -> io::Result<()> {
+        self.scroll_region = None;
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        Ok(self.screen.area.as_size())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn enable_raw_mode(&mut self) -> io::Result<()> {
+        self.raw_mode = true;
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> io::Result<()> {
+        self.raw_mode = false;
+        Ok(())
+    }
+
+    fn is_raw_mode_enabled(&self) -> io::Result<bool> {
+        Ok(self.raw_mode)
+    }
+
+    fn write_raw(&mut self, _content: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn read_event_timeout(&self, _timeout: Duration) -> Option<(Self::Event, bool)> {
+        let event = self.events.borrow_mut().pop_front()?;
+        let should_exit = matches!(
+            event,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            })
+        );
+        Some((event, should_exit))
+    }
+
+    fn resize_event(&self, event: &Self::Event) -> Option<Size> {
+        match event {
+            Event::Resize(width, height) => Some(Size {
+                width: *width,
+                height: *height,
+            }),
+            _ => None,
+        }
+    }
+
+    fn suspend_event(&self, event: &Self::Event) -> bool {
+        matches!(
+            event,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('z'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            })
+        )
+    }
+
+    fn query(&mut self, _query: &str, _timeout: Duration) -> io::Result<Option<String>> {
+        Ok(None)
+    }
+}
@@ -1,14 +1,16 @@
 use std::{
     cmp,
+    fmt::Display,
     ops::{Index, IndexMut},
 };
 
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 use crate::{
     buffer::Cell,
     layout::{Position, Rect},
-    style::Style,
+    style::{Style, StyledString},
 };
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
@@ -81,9 +83,9 @@ impl Buffer {
                 updates.push((x, y, &next_buffer[i]))
             }
 
-            to_skip = current.symbol().width().saturating_sub(1);
+            to_skip = current.width().saturating_sub(1);
 
-            let affected_width = cmp::max(current.symbol().width(), previous.symbol().width());
+            let affected_width = cmp::max(current.width(), previous.width());
             invalidated = cmp::max(affected_width, invalidated).saturating_sub(1)
         }
 
@@ -101,6 +103,144 @@ impl Buffer {
         }
     }
 
+    /// Write a string starting at the given position, clipped at the right edge of the buffer.
+    /// Returns the position after the last cell written. See [set_stringn](Self::set_stringn).
+    pub fn set_string<S, T>(&mut self, x: u16, y: u16, string: S, style: T) -> Position
+    where
+        S: AsRef<str>,
+        T: Into<Style>,
+    {
+        self.set_stringn(x, y, string, usize::MAX, style)
+    }
+
+    /// Write a string starting at the given position, using at most `max_width` columns and
+    /// clipped at the right edge of the buffer. Returns the position after the last cell written.
+    ///
+    /// The string is split into grapheme clusters, each taking up as many cells as it is wide.
+    /// The cells covered by the rest of a wide grapheme are marked as [skip](Cell::skip). A
+    /// grapheme which doesn't fit is left out along with everything after it, and control
+    /// characters are dropped. Wide graphemes which are only partly written over are replaced by
+    /// blank cells.
+    pub fn set_stringn<S, T>(
+        &mut self,
+        x: u16,
+        y: u16,
+        string: S,
+        max_width: usize,
+        style: T,
+    ) -> Position
+    where
+        S: AsRef<str>,
+        T: Into<Style>,
+    {
+        let style = style.into();
+        let mut position = Position { x, y };
+        if !self.area.contains(position) {
+            return position;
+        }
+
+        let mut remaining = max_width.min(usize::from(self.area.right() - x));
+        let graphemes = string
+            .as_ref()
+            .graphemes(true)
+            .filter(|grapheme| !grapheme.contains(char::is_control));
+        for grapheme in graphemes {
+            let width = grapheme.width();
+            if width == 0 {
+                continue;
+            }
+            if width > remaining {
+                break;
+            }
+
+            let next = position.x + width as u16;
+            self.blank_overwritten(position, next - position.x);
+            self[position].set_symbol(grapheme).set_style(style);
+            for skipped in position.x + 1..next {
+                let cell = &mut self[(skipped, y)];
+                cell.reset();
+                cell.skip = true;
+            }
+            position.x = next;
+            remaining -= width;
+        }
+
+        position
+    }
+
+    /// Write a [StyledString] starting at the given position, clipped at the right edge of the
+    /// buffer. Returns the position after the last cell written. See
+    /// [set_stringn](Self::set_stringn).
+    pub fn set_styled<D: Display>(&mut self, x: u16, y: u16, string: &StyledString<D>) -> Position {
+        self.set_string(x, y, string.content().to_string(), *string.style())
+    }
+
+    /// Copy the cells of another buffer over this one at the positions they have in the other
+    /// buffer, clipped to this buffer. See [blit](Self::blit).
+    pub fn merge(&mut self, other: &Self) {
+        self.blit(other, other.area, other.area.as_position());
+    }
+
+    /// Copy the cells of `src` within `src_rect` to this buffer, with the top-left cell of
+    /// `src_rect` at `dest`. Cells which would land outside of this buffer are left out.
+    ///
+    /// [Empty](Cell::EMPTY) cells are transparent and leave the cells below them as they were,
+    /// so a component can be rendered once into its own buffer and composited into each frame.
+    /// Wide graphemes which would be cut off by the edge of `src_rect` or this buffer are replaced
+    /// by a blank cell, as are the cells covered by a wide grapheme which isn't copied and the
+    /// rest of any wide grapheme in this buffer which is only partly copied over.
+    pub fn blit(&mut self, src: &Self, src_rect: Rect, dest: Position) {
+        let src_rect = src.area.intersection(src_rect);
+
+        for y in src_rect.top()..src_rect.bottom() {
+            let dest_y = dest.y.saturating_add(y - src_rect.y);
+            // The columns still covered by the last wide grapheme copied
+            let mut covered = 0;
+            for x in src_rect.left()..src_rect.right() {
+                let cell = &src[(x, y)];
+                let position = Position {
+                    x: dest.x.saturating_add(x - src_rect.x),
+                    y: dest_y,
+                };
+                if *cell == Cell::EMPTY || !self.area.contains(position) {
+                    covered = 0;
+                    continue;
+                }
+
+                let cell = if cell.skip {
+                    if covered > 0 {
+                        covered -= 1;
+                        cell.clone()
+                    } else {
+                        blank(cell)
+                    }
+                } else {
+                    let extra = u16::try_from(cell.width().saturating_sub(1)).unwrap_or(u16::MAX);
+                    let fits = x
+                        .checked_add(extra)
+                        .is_some_and(|last| last < src_rect.right())
+                        && position
+                            .x
+                            .checked_add(extra)
+                            .is_some_and(|last| last < self.area.right());
+                    if fits {
+                        covered = extra;
+                        cell.clone()
+                    } else {
+                        covered = 0;
+                        blank(cell)
+                    }
+                };
+                // Skip cells are only copied right after the wide grapheme they belong to
+                if !cell.skip {
+                    let width = u16::try_from(cell.width().max(1)).unwrap_or(u16::MAX);
+                    self.blank_overwritten(position, width);
+                }
+                self[position] = cell;
+            }
+        }
+    }
+
     pub fn resize(&mut self, area: Rect) {
         let length = area.area() as usize;
         if self.content.len() > length {
@@ -111,6 +251,43 @@ impl Buffer {
 
         self.area = area;
     }
+
+    /// Blank the wide graphemes which would only be partly overwritten by writing `width` cells
+    /// starting at `position`, so no part of them is left behind.
+    fn blank_overwritten(&mut self, position: Position, width: u16) {
+        let last = Position {
+            x: position.x.saturating_add(width - 1),
+            ..position
+        };
+        self.blank_wide_grapheme(position);
+        if last != position && self.area.contains(last) {
+            self.blank_wide_grapheme(last);
+        }
+    }
+
+    /// Replace the wide grapheme covering the given position, if there is one, with blank cells.
+    fn blank_wide_grapheme(&mut self, position: Position) {
+        let y = position.y;
+        let mut start = position.x;
+        while start > self.area.left() && self[(start, y)].skip {
+            start -= 1;
+        }
+
+        let head = &self[(start, y)];
+        let end = start.saturating_add(u16::try_from(head.width()).unwrap_or(u16::MAX));
+        if head.skip || end <= position.x || end - start < 2 {
+            return;
+        }
+
+        let blank = blank(head);
+        self[(start, y)] = blank.clone();
+        for x in start + 1..end.min(self.area.right()) {
+            if !self[(x, y)].skip {
+                break;
+            }
+            self[(x, y)] = blank.clone();
+        }
+    }
 }
 
 impl<P: Into<Position>> Index<P> for Buffer {
@@ -130,3 +307,149 @@ impl<P: Into<Position>> IndexMut<P> for Buffer {
         &mut self.content[index]
     }
 }
+
+/// A blank cell in the style of the given one, in place of part of a wide grapheme.
+fn blank(cell: &Cell) -> Cell {
+    Cell::new(Some(" ".to_string()), cell.fg, cell.bg, cell.attributes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Buffer;
+    use crate::{
+        layout::{Position, Rect},
+        style::{Color, Style, Stylize},
+    };
+
+    fn buffer(width: u16) -> Buffer {
+        Buffer::empty(Rect {
+            x: 0,
+            y: 0,
+            width,
+            height: 1,
+        })
+    }
+
+    fn symbols(buffer: &Buffer) -> Vec<&str> {
+        buffer.content.iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn set_string_marks_wide_cells() {
+        let mut buffer = buffer(6);
+        let end = buffer.set_string(0, 0, "a界b", Style::new().fg(Color::Red));
+        assert_eq!(end, Position { x: 4, y: 0 });
+        assert_eq!(symbols(&buffer), ["a", "界", " ", "b", " ", " "]);
+        assert!(buffer[(2, 0)].skip);
+        assert_eq!(buffer[(3, 0)].fg, Color::Red);
+    }
+
+    #[test]
+    fn blit_skips_empty_cells() {
+        let mut frame = buffer(6);
+        frame.set_string(0, 0, "......", Style::new());
+
+        let mut cached = buffer(4);
+        cached.set_string(0, 0, "ab", Style::new());
+        cached.set_string(3, 0, "c", Style::new());
+        frame.blit(&cached, cached.area, Position { x: 1, y: 0 });
+        assert_eq!(symbols(&frame), [".", "a", "b", ".", "c", "."]);
+
+        // Only part of the source is copied, and cells past the edge are left out
+        let mut frame = buffer(3);
+        frame.blit(
+            &cached,
+            Rect {
+                x: 1,
+                y: 0,
+                width: 3,
+                height: 1,
+            },
+            Position { x: 1, y: 0 },
+        );
+        assert_eq!(symbols(&frame), [" ", "b", " "]);
+
+        let mut merged = buffer(4);
+        merged.set_string(2, 0, "xy", Style::new());
+        merged.merge(&cached);
+        assert_eq!(symbols(&merged), ["a", "b", "x", "c"]);
+    }
+
+    #[test]
+    fn blit_blanks_cut_wide_graphemes() {
+        let mut cached = buffer(6);
+        cached.set_string(0, 0, "界a界b", Style::new());
+
+        // The first wide grapheme is cut at the left edge and the second at the right edge
+        let mut frame = buffer(4);
+        frame.set_string(0, 0, "....", Style::new());
+        frame.blit(
+            &cached,
+            Rect {
+                x: 1,
+                y: 0,
+                width: 3,
+                height: 1,
+            },
+            Position { x: 0, y: 0 },
+        );
+        assert_eq!(symbols(&frame), [" ", "a", " ", "."]);
+        assert!(frame.content.iter().all(|cell| !cell.skip));
+
+        // Wide graphemes which fit are copied whole
+        let mut frame = buffer(4);
+        frame.blit(&cached, cached.area, Position { x: 1, y: 0 });
+        assert_eq!(symbols(&frame), [" ", "界", " ", "a"]);
+        assert!(frame[(2, 0)].skip);
+    }
+
+    #[test]
+    fn overwriting_wide_graphemes_blanks_their_rest() {
+        let mut buffer = buffer(6);
+        buffer.set_string(0, 0, "界界界", Style::new());
+        buffer.set_string(1, 0, "x", Style::new());
+        buffer.set_string(2, 0, "y", Style::new());
+        assert_eq!(symbols(&buffer), [" ", "x", "y", " ", "界", " "]);
+        assert!(buffer.content[..4].iter().all(|cell| !cell.skip));
+        assert!(buffer[(5, 0)].skip);
+
+        // A wide grapheme written across the halves of two others
+        let mut buffer = self::buffer(6);
+        buffer.set_string(0, 0, "界界界", Style::new());
+        buffer.set_string(1, 0, "国", Style::new());
+        assert_eq!(symbols(&buffer), [" ", "国", " ", " ", "界", " "]);
+        assert!(buffer[(2, 0)].skip);
+        assert!(!buffer[(3, 0)].skip);
+    }
+
+    #[test]
+    fn blit_blanks_partly_covered_wide_graphemes() {
+        let mut cached = buffer(2);
+        cached.set_string(0, 0, "ab", Style::new());
+
+        let mut frame = buffer(6);
+        frame.set_string(0, 0, "界界界", Style::new());
+        frame.blit(&cached, cached.area, Position { x: 1, y: 0 });
+        assert_eq!(symbols(&frame), [" ", "a", "b", " ", "界", " "]);
+        assert!(frame.content[..4].iter().all(|cell| !cell.skip));
+    }
+
+    #[test]
+    fn set_stringn_clips() {
+        let mut buffer = buffer(4);
+        // The wide grapheme doesn't fit in the last column
+        assert_eq!(buffer.set_string(1, 0, "ab界", Style::new()).x, 3);
+        assert_eq!(symbols(&buffer), [" ", "a", "b", " "]);
+
+        let mut buffer = self::buffer(4);
+        assert_eq!(
+            buffer.set_stringn(0, 0, "e\u{301}xyz", 2, Style::new()).x,
+            2
+        );
+        assert_eq!(symbols(&buffer), ["e\u{301}", "x", " ", " "]);
+
+        let end = buffer.set_styled(2, 0, &"long".with(Color::Blue));
+        assert_eq!(end.x, 4);
+        assert_eq!(symbols(&buffer), ["e\u{301}", "x", "l", "o"]);
+    }
+}
@@ -1,3 +1,5 @@
+use unicode_width::UnicodeWidthStr;
+
 use crate::style::{Attributes, Color, Style};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -29,6 +31,19 @@ impl Cell {
         self.symbol.as_ref().map_or(" ", |s| s.as_str())
     }
 
+    /// The number of columns the symbol occupies.
+    ///
+    /// Symbols which are escape sequences (e.g. inline images) count as a single column. The
+    /// cells they cover should be marked as [skip](Self::skip).
+    pub fn width(&self) -> usize {
+        let symbol = self.symbol();
+        if symbol.starts_with('\x1b') {
+            1
+        } else {
+            symbol.width()
+        }
+    }
+
     pub fn set_symbol(&mut self, symbol: &str) -> &mut Self {
         self.symbol = Some(String::from(symbol));
         self
@@ -5,15 +5,49 @@
 use crate::{buffer::Buffer, layout::Rect};
 
 pub mod confirmation;
+pub mod copy;
+pub mod image;
+pub mod tail;
 
 /// Component is the building block of `tdrop` rendering
 pub trait Component {
     /// Render the component to the terminal
     fn render(self, area: Rect, buffer: &mut Buffer);
+
+    /// The number of rows the component needs when rendered at the given width, if known.
+    ///
+    /// This is used to size [Viewport::Fit](crate::terminal::Viewport::Fit) viewports.
+    fn height(&self, _width: u16) -> Option<u16> {
+        None
+    }
+}
+
+/// A buffer rendered ahead of time, e.g. to cache a component which rarely changes, is drawn by
+/// copying it into the area. Its [empty](crate::buffer::Cell::EMPTY) cells are transparent, see
+/// [Buffer::blit].
+impl Component for &Buffer {
+    fn render(self, area: Rect, buffer: &mut Buffer) {
+        let src_rect = Rect {
+            width: self.area.width.min(area.width),
+            height: self.area.height.min(area.height),
+            ..self.area
+        };
+        buffer.blit(self, src_rect, area.as_position());
+    }
+
+    fn height(&self, _width: u16) -> Option<u16> {
+        Some(self.area.height)
+    }
 }
 
 pub trait StatefulComponent {
     type State: Sized;
 
     fn render(self, area: Rect, buffer: &mut Buffer, state: &mut Self::State);
+
+    /// The number of rows the component needs when rendered at the given width with the given
+    /// state, if known. See [Component::height].
+    fn height(&self, _width: u16, _state: &Self::State) -> Option<u16> {
+        None
+    }
 }
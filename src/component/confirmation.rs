@@ -26,4 +26,8 @@ impl StatefulComponent for Confirmation {
             buffer[(area.x, area.y)].set_symbol("r");
         }
     }
+
+    fn height(&self, _width: u16, _state: &Self::State) -> Option<u16> {
+        Some(1)
+    }
 }
@@ -0,0 +1,81 @@
+//! Support for copying the selected entry of a component to the clipboard.
+//!
+//! Component states with a selection (e.g. the selected row of a table) implement [Copyable],
+//! and applications call [Terminal::copy_selected](crate::terminal::Terminal::copy_selected) when
+//! [COPY_KEY] is pressed. [CopyHint] renders the matching "press y to copy" hint.
+
+use crate::{
+    buffer::Buffer,
+    component::Component,
+    layout::Rect,
+    style::{Attribute, Style},
+};
+
+/// The key conventionally used to copy the selected entry.
+pub const COPY_KEY: char = 'y';
+
+/// Implemented by component states which have a selected entry that can be copied.
+pub trait Copyable {
+    /// The text to copy for the selected entry, or [None] if nothing is selected.
+    fn selected_text(&self) -> Option<String>;
+}
+
+/// A hint telling the user how to copy the selected entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CopyHint {
+    /// Whether the entry was just copied, in which case a confirmation is shown instead.
+    pub copied: bool,
+    /// The [Style] of the hint.
+    pub style: Style,
+}
+
+impl Default for CopyHint {
+    fn default() -> Self {
+        Self {
+            copied: false,
+            style: Style::new().attribute(Attribute::Dim),
+        }
+    }
+}
+
+impl CopyHint {
+    /// Create a new [CopyHint] with the default dimmed style.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether the entry was just copied and return the modified value.
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub fn copied(mut self, copied: bool) -> Self {
+        self.copied = copied;
+        self
+    }
+
+    /// Set the [Style] of the hint and return the modified value.
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub fn style<S: Into<Style>>(mut self, style: S) -> Self {
+        self.style = style.into();
+        self
+    }
+}
+
+impl Component for CopyHint {
+    fn render(self, area: Rect, buffer: &mut Buffer) {
+        let area = buffer.area.intersection(area);
+        if area.height == 0 {
+            return;
+        }
+
+        let text = if self.copied {
+            String::from("copied to clipboard")
+        } else {
+            format!("press {COPY_KEY} to copy")
+        };
+
+        buffer.set_stringn(area.x, area.y, text, usize::from(area.width), self.style);
+    }
+
+    fn height(&self, _width: u16) -> Option<u16> {
+        Some(1)
+    }
+}
@@ -0,0 +1,510 @@
+//! Inline image rendering.
+//!
+//! [Image] renders RGBA pixel data using the kitty graphics protocol or sixel when the terminal
+//! supports them, and Unicode half blocks otherwise. Graphics protocol output is anchored to the
+//! top left cell of the image while the remaining cells it covers are marked as
+//! [skip](crate::buffer::Cell::skip), so [Buffer::diff] leaves them alone and an unchanged image
+//! isn't retransmitted.
+//!
+//! Images drawn with the kitty graphics protocol stay on screen until they are deleted, which the
+//! [Terminal](crate::terminal::Terminal) does once the cell they are anchored to is drawn over or
+//! cleared.
+
+use std::fmt::Write;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use crate::{
+    base64,
+    buffer::Buffer,
+    component::Component,
+    layout::{Rect, Size},
+    style::Color,
+    terminal::Capabilities,
+};
+
+/// The cell size in pixels assumed when the terminal doesn't report one.
+const DEFAULT_CELL_SIZE: Size = Size {
+    width: 10,
+    height: 20,
+};
+
+/// The maximum payload of a single kitty graphics protocol escape sequence.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// Pixels with an alpha value below this are treated as transparent.
+const ALPHA_THRESHOLD: u8 = 128;
+
+/// The method used to draw an [Image].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GraphicsProtocol {
+    /// The kitty graphics protocol.
+    Kitty,
+    /// Sixel graphics.
+    Sixel,
+    /// Unicode half block characters, which work in any terminal with truecolor support.
+    #[default]
+    HalfBlocks,
+}
+
+impl GraphicsProtocol {
+    /// The best protocol supported by the terminal, falling back to
+    /// [HalfBlocks](Self::HalfBlocks).
+    pub fn detect(capabilities: &Capabilities) -> Self {
+        if capabilities.kitty_graphics {
+            Self::Kitty
+        } else if capabilities.sixel {
+            Self::Sixel
+        } else {
+            Self::HalfBlocks
+        }
+    }
+}
+
+/// An image made of RGBA pixel data, scaled to fit the area it is rendered into while preserving
+/// its aspect ratio.
+///
+/// ```
+/// use tdrop::component::image::Image;
+///
+/// // A 2x1 image with a red and a blue pixel
+/// let pixels = [255, 0, 0, 255, 0, 0, 255, 255];
+/// let image = Image::new(2, 1, &pixels);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Image<'a> {
+    width: u32,
+    height: u32,
+    pixels: &'a [u8],
+    protocol: GraphicsProtocol,
+    cell_size: Size,
+    id: u32,
+}
+
+impl<'a> Image<'a> {
+    /// Create a new image from row-major RGBA pixel data, drawn with half blocks by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pixels` isn't exactly `width * height * 4` bytes long.
+    pub fn new(width: u32, height: u32, pixels: &'a [u8]) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize * 4,
+            "image data must be width * height RGBA pixels"
+        );
+
+        Self {
+            width,
+            height,
+            pixels,
+            protocol: GraphicsProtocol::default(),
+            cell_size: DEFAULT_CELL_SIZE,
+            id: default_id(width, height, pixels),
+        }
+    }
+
+    /// Set the [GraphicsProtocol] and cell size from the terminal's [Capabilities] and return
+    /// the modified value.
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub fn capabilities(mut self, capabilities: &Capabilities) -> Self {
+        self.protocol = GraphicsProtocol::detect(capabilities);
+        self.cell_size = capabilities.cell_size.unwrap_or(DEFAULT_CELL_SIZE);
+        self
+    }
+
+    /// Set the [GraphicsProtocol] and return the modified value.
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub fn protocol(mut self, protocol: GraphicsProtocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Set the size of a terminal cell in pixels and return the modified value.
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub fn cell_size(mut self, cell_size: Size) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Set the kitty graphics protocol image id and return the modified value.
+    ///
+    /// By default the id is derived from the pixel data, so the same image rendered again keeps
+    /// its id while different images get distinct ones. Rendering an image replaces any earlier
+    /// image with the same id.
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub fn id(mut self, id: u32) -> Self {
+        self.id = id;
+        self
+    }
+
+    /// The size in pixels of the image scaled to fit `area`.
+    fn fit(&self, area: Rect) -> (u32, u32) {
+        let cell_width = f64::from(self.cell_size.width.max(1));
+        let cell_height = f64::from(self.cell_size.height.max(1));
+
+        let scale = (f64::from(area.width) * cell_width / f64::from(self.width))
+            .min(f64::from(area.height) * cell_height / f64::from(self.height));
+
+        (
+            ((f64::from(self.width) * scale) as u32).max(1),
+            ((f64::from(self.height) * scale) as u32).max(1),
+        )
+    }
+
+    /// The number of cells covered by an image of the given size in pixels.
+    fn cells(&self, area: Rect, (width, height): (u32, u32)) -> (u16, u16) {
+        let columns = width.div_ceil(u32::from(self.cell_size.width.max(1)));
+        let rows = height.div_ceil(u32::from(self.cell_size.height.max(1)));
+
+        (
+            u16::try_from(columns).map_or(area.width, |c| c.min(area.width)),
+            u16::try_from(rows).map_or(area.height, |r| r.min(area.height)),
+        )
+    }
+
+    /// Resample the image to the given size using nearest neighbour interpolation.
+    fn resize(&self, width: u32, height: u32) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+
+        for y in 0..height {
+            let source_y = (u64::from(y) * u64::from(self.height) / u64::from(height)) as usize;
+            for x in 0..width {
+                let source_x = (u64::from(x) * u64::from(self.width) / u64::from(width)) as usize;
+                let index = (source_y * self.width as usize + source_x) * 4;
+                pixels.extend_from_slice(&self.pixels[index..index + 4]);
+            }
+        }
+
+        pixels
+    }
+
+    fn kitty_sequence(&self, columns: u16, rows: u16) -> String {
+        let data = base64::encode(self.pixels);
+        let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+        let mut sequence = String::new();
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            let more = u8::from(i + 1 < chunks.len());
+            if i == 0 {
+                // Transmit and display RGBA data without moving the cursor or sending a response
+                let _ = write!(
+                    sequence,
+                    "\x1b_Ga=T,f=32,s={},v={},c={columns},r={rows},i={},p=1,C=1,q=2,m={more};",
+                    self.width, self.height, self.id
+                );
+            } else {
+                let _ = write!(sequence, "\x1b_Gm={more};");
+            }
+            sequence.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+            sequence.push_str("\x1b\\");
+        }
+
+        sequence
+    }
+
+    fn sixel_sequence(&self, width: u32, height: u32) -> String {
+        let pixels = self.resize(width, height);
+        let palette: Vec<Option<u8>> = pixels.chunks(4).map(quantize).collect();
+
+        // Transparent background, 1:1 pixel aspect ratio and the image size
+        let mut sequence = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+
+        let mut used = [false; 216];
+        for index in palette.iter().flatten() {
+            used[usize::from(*index)] = true;
+        }
+        for (index, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+            let level = |l: usize| l * 100 / 5;
+            let _ = write!(
+                sequence,
+                "#{index};2;{};{};{}",
+                level(index / 36),
+                level(index / 6 % 6),
+                level(index % 6)
+            );
+        }
+
+        let width = width as usize;
+        for band in (0..height as usize).step_by(6) {
+            let rows = band..(band + 6).min(height as usize);
+            let mut colors: Vec<u8> = rows
+                .clone()
+                .flat_map(|y| {
+                    palette[y * width..(y + 1) * width]
+                        .iter()
+                        .flatten()
+                        .copied()
+                })
+                .collect();
+            colors.sort_unstable();
+            colors.dedup();
+
+            for color in colors {
+                let _ = write!(sequence, "#{color}");
+
+                let mut run: Option<(u8, usize)> = None;
+                for x in 0..width {
+                    let bits = rows.clone().enumerate().fold(0u8, |bits, (bit, y)| {
+                        if palette[y * width + x] == Some(color) {
+                            bits | 1 << bit
+                        } else {
+                            bits
+                        }
+                    });
+                    let sixel = 63 + bits;
+
+                    run = match run {
+                        Some((previous, count)) if previous == sixel => Some((sixel, count + 1)),
+                        Some((previous, count)) => {
+                            push_sixel_run(&mut sequence, previous, count);
+                            Some((sixel, 1))
+                        }
+                        None => Some((sixel, 1)),
+                    };
+                }
+                if let Some((sixel, count)) = run {
+                    push_sixel_run(&mut sequence, sixel, count);
+                }
+
+                sequence.push('$');
+            }
+            sequence.push('-');
+        }
+
+        sequence.push_str("\x1b\\");
+        sequence
+    }
+
+    fn render_half_blocks(&self, area: Rect, buffer: &mut Buffer) {
+        let (width, height) = self.fit(area);
+        let cell_width = u32::from(self.cell_size.width.max(1));
+        // Each cell holds two vertically stacked pixels
+        let half_height = u32::from(self.cell_size.height.max(2)) / 2;
+
+        let columns = (width / cell_width).clamp(1, u32::from(area.width));
+        let pixel_rows = (height / half_height).clamp(1, u32::from(area.height) * 2);
+        let pixels = self.resize(columns, pixel_rows);
+        let color = |x: u32, y: u32| {
+            if y >= pixel_rows {
+                return None;
+            }
+            let index = (y * columns + x) as usize * 4;
+            let [r, g, b, a] = pixels[index..index + 4] else {
+                return None;
+            };
+            (a >= ALPHA_THRESHOLD).then_some(Color::Rgb { r, g, b })
+        };
+
+        for row in 0..pixel_rows.div_ceil(2) {
+            for column in 0..columns {
+                let cell = &mut buffer[(area.x + column as u16, area.y + row as u16)];
+                match (color(column, row * 2), color(column, row * 2 + 1)) {
+                    (Some(top), bottom) => {
+                        cell.set_symbol("▀");
+                        cell.fg = top;
+                        cell.bg = bottom.unwrap_or(Color::Reset);
+                    }
+                    (None, Some(bottom)) => {
+                        cell.set_symbol("▄");
+                        cell.fg = bottom;
+                        cell.bg = Color::Reset;
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+    }
+}
+
+impl Component for Image<'_> {
+    fn render(self, area: Rect, buffer: &mut Buffer) {
+        let area = buffer.area.intersection(area);
+        if area.area() == 0 || self.width == 0 || self.height == 0 {
+            return;
+        }
+
+        let size = self.fit(area);
+        let (columns, rows) = self.cells(area, size);
+        let sequence = match self.protocol {
+            GraphicsProtocol::HalfBlocks => return self.render_half_blocks(area, buffer),
+            GraphicsProtocol::Kitty => self.kitty_sequence(columns, rows),
+            GraphicsProtocol::Sixel => self.sixel_sequence(size.0, size.1),
+        };
+
+        for y in area.y..area.y + rows {
+            for x in area.x..area.x + columns {
+                let cell = &mut buffer[(x, y)];
+                cell.reset();
+                cell.skip = true;
+            }
+        }
+
+        let anchor = &mut buffer[(area.x, area.y)];
+        anchor.set_symbol(&sequence);
+        anchor.skip = false;
+    }
+}
+
+/// An image id derived from the image data. Kitty ids must be nonzero.
+fn default_id(width: u32, height: u32, pixels: &[u8]) -> u32 {
+    let mut hasher = DefaultHasher::new();
+    (width, height, pixels).hash(&mut hasher);
+    (hasher.finish() as u32).max(1)
+}
+
+/// The id of the kitty graphics protocol image drawn by a cell's symbol, if it draws one.
+fn kitty_id(symbol: &str) -> Option<u32> {
+    let params = symbol.strip_prefix("\x1b_Ga=T,")?;
+    let params = &params[..params.find(';')?];
+    params
+        .split(',')
+        .find_map(|param| param.strip_prefix("i=")?.parse().ok())
+}
+
+/// The sequences deleting the kitty graphics protocol images anchored in `previous` whose anchor
+/// is drawn over in `next`, along with their placements.
+pub(crate) fn kitty_deletions(previous: &Buffer, next: &Buffer) -> String {
+    let mut deletions = String::new();
+    for (before, after) in previous.content.iter().zip(&next.content) {
+        if before.symbol() == after.symbol() {
+            continue;
+        }
+        if let Some(id) = kitty_id(before.symbol()) {
+            let _ = write!(deletions, "\x1b_Ga=d,d=i,i={id},q=2\x1b\\");
+        }
+    }
+    deletions
+}
+
+/// Map an RGBA pixel onto the 6x6x6 color cube used as the sixel palette, or [None] if it is
+/// transparent.
+fn quantize(pixel: &[u8]) -> Option<u8> {
+    let [r, g, b, a] = *pixel else {
+        return None;
+    };
+    let level = |c: u8| (u16::from(c) * 5 + 127) / 255;
+
+    (a >= ALPHA_THRESHOLD).then(|| (level(r) * 36 + level(g) * 6 + level(b)) as u8)
+}
+
+fn push_sixel_run(sequence: &mut String, sixel: u8, count: usize) {
+    if count > 3 {
+        let _ = write!(sequence, "!{count}{}", char::from(sixel));
+    } else {
+        for _ in 0..count {
+            sequence.push(char::from(sixel));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GraphicsProtocol, Image, kitty_deletions};
+    use crate::{
+        buffer::Buffer,
+        component::Component,
+        layout::{Rect, Size},
+        style::Color,
+    };
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    fn area(width: u16, height: u16) -> Rect {
+        Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn half_blocks() {
+        // A 1x2 image of a red pixel above a blue one fills a single cell
+        let pixels = [RED, BLUE].concat();
+        let mut buffer = Buffer::empty(area(4, 2));
+        Image::new(1, 2, &pixels)
+            .cell_size(Size {
+                width: 10,
+                height: 20,
+            })
+            .render(area(1, 1), &mut buffer);
+
+        let cell = &buffer[(0, 0)];
+        assert_eq!(cell.symbol(), "▀");
+        assert_eq!(cell.fg, Color::Rgb { r: 255, g: 0, b: 0 });
+        assert_eq!(cell.bg, Color::Rgb { r: 0, g: 0, b: 255 });
+        assert_eq!(buffer[(1, 0)].symbol(), " ");
+    }
+
+    #[test]
+    fn graphics_protocol_skips_covered_cells() {
+        let pixels = [RED; 4].concat();
+        let mut buffer = Buffer::empty(area(4, 2));
+        Image::new(2, 2, &pixels)
+            .protocol(GraphicsProtocol::Kitty)
+            .cell_size(Size {
+                width: 1,
+                height: 1,
+            })
+            .render(area(4, 2), &mut buffer);
+
+        assert!(buffer[(0, 0)].symbol().starts_with("\x1b_Ga=T"));
+        assert!(!buffer[(0, 0)].skip);
+        assert!(buffer[(1, 0)].skip && buffer[(0, 1)].skip && buffer[(1, 1)].skip);
+        assert!(!buffer[(2, 0)].skip);
+
+        // Only the anchor is drawn, and cells after it aren't swallowed by its length
+        let updates = Buffer::empty(buffer.area).diff(&buffer);
+        assert_eq!(updates.len(), 1);
+        let mut next = buffer.clone();
+        next[(3, 1)].set_symbol("x");
+        assert_eq!(buffer.diff(&next).len(), 1);
+    }
+
+    #[test]
+    fn sixel_sequence() {
+        let pixels = [RED, BLUE].concat();
+        let sequence = Image::new(2, 1, &pixels)
+            .protocol(GraphicsProtocol::Sixel)
+            .sixel_sequence(2, 1);
+
+        assert_eq!(
+            sequence,
+            "\x1bP0;1;0q\"1;1;2;1#5;2;0;0;100#180;2;100;0;0#5?@$#180@?$-\x1b\\"
+        );
+    }
+
+    #[test]
+    fn kitty_images_are_deleted_when_drawn_over() {
+        let red = [RED; 4].concat();
+        let blue = [BLUE; 4].concat();
+        let kitty = |pixels| {
+            Image::new(2, 2, pixels)
+                .protocol(GraphicsProtocol::Kitty)
+                .cell_size(Size {
+                    width: 1,
+                    height: 1,
+                })
+        };
+        assert_eq!(kitty(&red).id, kitty(&red).id);
+        assert_ne!(kitty(&red).id, kitty(&blue).id);
+
+        let mut shown = Buffer::empty(area(4, 2));
+        kitty(&red).render(area(4, 2), &mut shown);
+        assert_eq!(kitty_deletions(&shown, &shown.clone()), "");
+
+        let id = kitty(&red).id;
+        let mut replaced = Buffer::empty(area(4, 2));
+        kitty(&blue).render(area(4, 2), &mut replaced);
+        assert_eq!(
+            kitty_deletions(&shown, &replaced),
+            format!("\x1b_Ga=d,d=i,i={id},q=2\x1b\\")
+        );
+        assert_eq!(
+            kitty_deletions(&shown, &Buffer::empty(shown.area)),
+            format!("\x1b_Ga=d,d=i,i={id},q=2\x1b\\")
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! A component showing the last lines of output as it arrives, such as that of a child process.
+//!
+//! [Terminal::run_command](crate::terminal::Terminal::run_command) uses [Tail] to show the output
+//! of a command while it runs.
+
+use crate::{
+    buffer::Buffer,
+    component::StatefulComponent,
+    layout::Rect,
+    style::{AnsiParser, StyledString},
+};
+
+/// Shows the last lines of a [TailState] which fit, cutting off lines which are too long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tail {
+    /// The number of lines shown.
+    pub height: u16,
+}
+
+impl Tail {
+    /// Create a new [Tail] showing the given number of lines.
+    pub const fn new(height: u16) -> Self {
+        Self { height }
+    }
+}
+
+/// The lines shown by a [Tail], which keeps every line pushed so the full output is available
+/// afterwards.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TailState {
+    lines: Vec<Vec<StyledString<String>>>,
+    parser: AnsiParser,
+}
+
+impl TailState {
+    /// Create an empty [TailState].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a line of output, without its line ending. ANSI colors and attributes in the line
+    /// are kept, see [AnsiParser].
+    pub fn push(&mut self, line: &str) {
+        let line = self.parser.parse(line);
+        self.lines.push(line);
+    }
+
+    /// Every line pushed so far, oldest first.
+    pub fn lines(&self) -> &[Vec<StyledString<String>>] {
+        &self.lines
+    }
+}
+
+impl StatefulComponent for Tail {
+    type State = TailState;
+
+    fn render(self, area: Rect, buffer: &mut Buffer, state: &mut Self::State) {
+        let area = buffer.area.intersection(area);
+        let shown = usize::from(self.height.min(area.height));
+        let start = state.lines.len().saturating_sub(shown);
+
+        for (y, line) in (area.top()..area.bottom()).zip(&state.lines[start..]) {
+            let mut x = area.left();
+            for string in line {
+                let max_width = usize::from(area.right().saturating_sub(x));
+                x = buffer
+                    .set_stringn(x, y, string.content(), max_width, *string.style())
+                    .x;
+            }
+        }
+    }
+
+    fn height(&self, _width: u16, _state: &Self::State) -> Option<u16> {
+        Some(self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Tail, TailState};
+    use crate::{buffer::Buffer, component::StatefulComponent, layout::Rect};
+
+    #[test]
+    fn shows_last_lines() {
+        let mut state = TailState::new();
+        for line in ["one", "two", "three is too long"] {
+            state.push(line);
+        }
+
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width: 8,
+            height: 2,
+        };
+        let mut buffer = Buffer::empty(area);
+        Tail::new(2).render(area, &mut buffer, &mut state);
+        let text: String = buffer.content.iter().map(|cell| cell.symbol()).collect();
+        assert_eq!(text, "two     three is");
+        assert_eq!(state.lines().len(), 3);
+    }
+}
@@ -13,8 +13,8 @@ impl From<(u16, u16)> for Position {
     }
 }
 
-impl Into<(u16, u16)> for Position {
-    fn into(self) -> (u16, u16) {
-        (self.x, self.y)
+impl From<Position> for (u16, u16) {
+    fn from(value: Position) -> Self {
+        (value.x, value.y)
     }
 }
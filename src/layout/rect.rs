@@ -67,11 +67,12 @@ impl Rect {
             && self.bottom() > other.y
     }
 
+    /// Returns true if the position is inside the `Rect`.
     pub const fn contains(self, position: Position) -> bool {
         position.x >= self.x
-            && position.x <= self.right()
+            && position.x < self.right()
             && position.y >= self.y
-            && position.y <= self.bottom()
+            && position.y < self.bottom()
     }
 }
 
@@ -44,7 +44,7 @@ impl Width {
 
     /// Check whether this [Width] intersects with another [Width]
     pub fn intersects(&self, other: &Self) -> bool {
-        return other.offset >= self.offset && other.offset < self.offset + self.width;
+        other.offset >= self.offset && other.offset < self.offset + self.width
     }
 
     /// Returns the inersection between this [Width] and another [Width] as a [Width].
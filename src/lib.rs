@@ -13,6 +13,7 @@
 //!
 //! `tdrop` is based on a hybrid of immediate rendering and terminal scrollback. Although it is
 //! counter-intuitive for CLIs, it puts the terminal in raw mode for the duration of the runtime.
+//! Alternatively, [init_lazy] only enters raw mode while interactive components are active.
 //!
 //! ## Basic Components
 //! For basic components such as [Span](component::Span) which implement the
@@ -23,7 +24,8 @@
 //! For stateful components such as [Confirmation](component::Confirmation) which implement the
 //! [StatefulComponent](component::StatefulComponent) trait, the rendering process operates a bit
 //! differently. The [Terminal](terminal::Terminal) will re-render the component at a fixed
-//! framerate, calling a user-provided closure to update the state of the component.
+//! framerate, calling a user-provided closure to update the state of the component (see
+//! [run_stateful](terminal::Terminal::run_stateful)).
 //!
 //! It's important to note that this closure should only poll for events (non-blocking), as
 //! otherwise it interferes with the ability of the terminal to respond to control sequences.
@@ -33,22 +35,26 @@
 //!
 //! ## Important Types
 //! * [Terminal](terminal::Terminal) - Provides shared abstraction over the terminal for use by
-//! [Widgets](widgets::Widget) and application code.
+//!   [Widgets](widgets::Widget) and application code.
 //! * [Width](layout::Width) - Defines an area of the terminal in which things will be rendered.
 //! * [Style](style::Style) - A defined output style including foreground, background, and
-//! underline [Color](style::Color) alongside [Attributes](style::Attributes)
+//!   underline [Color](style::Color) alongside [Attributes](style::Attributes)
 //!
 //! ## Code Examples
 //!
 
-use std::io::{self, Stdout};
+use std::io;
 
-use crossterm::{
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode},
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+
+use crate::{
+    backend::CrosstermBackend,
+    terminal::{Capabilities, DefaultColors, Output, PROBE_TIMEOUT, Terminal, TerminalBuilder},
 };
 
-use crate::{backend::CrosstermBackend, terminal::Terminal};
+mod base64;
+mod render;
+pub use render::{render_to_string, render_to_string_ansi};
 
 pub mod backend;
 pub mod buffer;
@@ -58,7 +64,7 @@ pub mod style;
 pub mod terminal;
 pub mod theme;
 
-pub type DefaultTerminal = Terminal<CrosstermBackend<Stdout>>;
+pub type DefaultTerminal = Terminal<CrosstermBackend<Output>>;
 
 pub fn run<F, R>(f: F) -> R
 where
@@ -66,6 +72,8 @@ where
 {
     let mut terminal = init();
     let result = f(&mut terminal);
+    // The terminal cleans up the viewport when it is dropped, which has to happen in raw mode
+    drop(terminal);
     restore();
     result
 }
@@ -75,11 +83,89 @@ pub fn init() -> DefaultTerminal {
 }
 
 pub fn try_init() -> io::Result<DefaultTerminal> {
+    try_init_with(TerminalBuilder::new())
+}
+
+/// Like [init], but the terminal is only put into raw mode while interactive or live components
+/// are active.
+///
+/// In between, normal [println!], reading from stdin and child processes behave as they would
+/// without `tdrop`. See [Terminal::enter_interactive].
+pub fn init_lazy() -> DefaultTerminal {
+    try_init_lazy().expect("failed to init terminal")
+}
+
+/// Like [try_init], but the terminal is only put into raw mode while interactive or live
+/// components are active. See [init_lazy].
+pub fn try_init_lazy() -> io::Result<DefaultTerminal> {
+    try_init_with(TerminalBuilder::new().lazy_raw_mode(true))
+}
+
+/// Like [init], but with the options set on the given [TerminalBuilder].
+///
+/// ```no_run
+/// use tdrop::terminal::{Output, TerminalBuilder, Viewport};
+///
+/// let terminal = tdrop::init_with(
+///     TerminalBuilder::new()
+///         .viewport(Viewport::Inline(3))
+///         .output(Output::Stderr),
+/// );
+/// ```
+pub fn init_with(builder: TerminalBuilder) -> DefaultTerminal {
+    try_init_with(builder).expect("failed to init terminal")
+}
+
+/// Like [try_init], but with the options set on the given [TerminalBuilder]. See [init_with].
+pub fn try_init_with(builder: TerminalBuilder) -> io::Result<DefaultTerminal> {
+    let lazy_raw_mode = builder.options.lazy_raw_mode;
+
+    // Raw mode is needed to read the replies to queries, even when it's otherwise lazy
     enable_raw_mode()?;
-    let backend = CrosstermBackend::new(std::io::stdout());
-    Terminal::new(backend)
+    let terminal = match build_terminal(builder) {
+        Ok(terminal) => terminal,
+        Err(err) => {
+            // The original error is more useful than any from leaving raw mode
+            let _ = disable_raw_mode();
+            return Err(err);
+        }
+    };
+
+    if lazy_raw_mode {
+        disable_raw_mode()?;
+    }
+    Ok(terminal)
+}
+
+/// Probe the terminal and create a [Terminal] for the builder's output, which must be in raw
+/// mode.
+fn build_terminal(builder: TerminalBuilder) -> io::Result<DefaultTerminal> {
+    let output = builder.output;
+    let mut backend = CrosstermBackend::new(output);
+
+    // Queries would end up in the output if it isn't a terminal. A failed query is treated the
+    // same as one the terminal didn't answer.
+    let (capabilities, colors) = if output.is_terminal() {
+        (
+            Capabilities::probe(&mut backend, PROBE_TIMEOUT)
+                .unwrap_or_else(|_| Capabilities::from_env()),
+            DefaultColors::query(&mut backend, PROBE_TIMEOUT).unwrap_or_default(),
+        )
+    } else {
+        (Capabilities::from_env(), DefaultColors::default())
+    };
+
+    builder
+        .capabilities(capabilities)
+        .colors(colors)
+        .build(backend)
 }
 
+/// Leave raw mode.
+///
+/// The viewport is cleaned up according to its [Cleanup](terminal::Cleanup) policy when the
+/// [Terminal] is dropped, which should happen before this is called. A terminal dropped after
+/// this leaves its viewport as it is.
 pub fn restore() {
     if let Err(err) = try_restore() {
         eprintln!("Failed to restore terminal: {err}");
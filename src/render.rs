@@ -0,0 +1,112 @@
+//! Rendering of components to strings, without a [Terminal](crate::terminal::Terminal).
+
+use crate::{
+    backend::Sgr,
+    buffer::{Buffer, Cell},
+    component::Component,
+    layout::Rect,
+    terminal::used_height,
+};
+
+/// The height components which don't report their own are rendered into before blank rows at
+/// the bottom are trimmed.
+const MAX_HEIGHT: u16 = 1024;
+
+/// Render a component at the given width into plain text, one line per row.
+///
+/// The component is rendered into a buffer sized to its [height](Component::height), or trimmed
+/// to the rows it used if it doesn't report one. Trailing whitespace is removed from each line and
+/// colors and attributes are dropped.
+///
+/// ```
+/// use tdrop::component::copy::CopyHint;
+///
+/// assert_eq!(tdrop::render_to_string(CopyHint::new(), 20), "press y to copy");
+/// ```
+pub fn render_to_string<C: Component>(component: C, width: u16) -> String {
+    let buffer = render(component, width);
+    rows(&buffer)
+        .map(|row| {
+            let mut line = String::new();
+            for cell in row.iter().filter(|cell| !cell.skip) {
+                // Escape sequences such as inline images have no text to show
+                if !cell.symbol().starts_with('\x1b') {
+                    line.push_str(cell.symbol());
+                }
+            }
+            line.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Like [render_to_string], but colors and attributes are kept as SGR escape sequences, which are
+/// reset at the end of every line.
+pub fn render_to_string_ansi<C: Component>(component: C, width: u16) -> String {
+    let buffer = render(component, width);
+    rows(&buffer)
+        .map(|row| {
+            let used = row
+                .iter()
+                .rposition(|cell| *cell != Cell::EMPTY)
+                .map_or(0, |last| last + 1);
+
+            let mut line = Vec::new();
+            let mut sgr = Sgr::default();
+            for cell in row[..used].iter().filter(|cell| !cell.skip) {
+                // Writing to a Vec can't fail
+                let _ = sgr.queue(&mut line, cell);
+                line.extend_from_slice(cell.symbol().as_bytes());
+            }
+            let _ = sgr.reset(&mut line);
+            String::from_utf8_lossy(&line).into_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Render a component into a buffer as tall as it needs.
+fn render<C: Component>(component: C, width: u16) -> Buffer {
+    let reported = component.height(width);
+    let area = Rect {
+        x: 0,
+        y: 0,
+        width,
+        height: reported.unwrap_or(MAX_HEIGHT),
+    };
+
+    let mut buffer = Buffer::empty(area);
+    component.render(area, &mut buffer);
+    let height = reported.unwrap_or_else(|| used_height(&buffer));
+    buffer.resize(Rect { height, ..area });
+    buffer
+}
+
+fn rows(buffer: &Buffer) -> impl Iterator<Item = &[Cell]> {
+    buffer.content.chunks(usize::from(buffer.area.width.max(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_to_string, render_to_string_ansi};
+    use crate::{buffer::Buffer, component::Component, layout::Rect, style::Color};
+
+    struct Greeting;
+
+    impl Component for Greeting {
+        fn render(self, area: Rect, buffer: &mut Buffer) {
+            buffer[(area.x, area.y)].set_symbol("h").fg = Color::Red;
+            buffer[(area.x + 1, area.y)].set_symbol("i");
+            buffer[(area.x, area.y + 1)].set_symbol("!");
+        }
+    }
+
+    #[test]
+    fn renders_used_rows() {
+        assert_eq!(render_to_string(Greeting, 10), "hi\n!");
+        assert_eq!(
+            render_to_string_ansi(Greeting, 10),
+            "\x1b[38;5;1;49mh\x1b[39;49mi\n!"
+        );
+    }
+}
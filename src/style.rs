@@ -25,22 +25,97 @@
 //!
 
 mod adaptive;
+mod ansi;
 mod attributes;
 mod color;
 mod styled;
 mod stylize;
 
 pub use adaptive::AdaptiveColor;
+pub use ansi::AnsiParser;
 pub use attributes::{Attribute, Attributes};
-pub use color::Color;
+pub use color::{Color, ColorMode};
 pub use styled::StyledString;
 pub use stylize::Stylize;
 
+use std::cell::Cell;
 use std::fmt::Display;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
-/// Returns whether the terminal background is light
+/// The backgrounds detected by the terminals which are still alive, oldest first.
+static BACKGROUNDS: Mutex<Vec<(usize, bool)>> = Mutex::new(Vec::new());
+static NEXT_BACKGROUND: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Whether the background of the terminal drawing on this thread is light.
+    static DRAWING: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// A background detected by a [Terminal](crate::terminal::Terminal), which [is_light] reports
+/// until it is dropped along with the terminal.
+#[derive(Debug)]
+pub(crate) struct Background {
+    id: usize,
+}
+
+impl Background {
+    pub(crate) fn detected(light: bool) -> Self {
+        let id = NEXT_BACKGROUND.fetch_add(1, Ordering::Relaxed);
+        backgrounds().push((id, light));
+        Self { id }
+    }
+}
+
+impl Drop for Background {
+    fn drop(&mut self) {
+        backgrounds().retain(|(id, _)| *id != self.id);
+    }
+}
+
+fn backgrounds() -> MutexGuard<'static, Vec<(usize, bool)>> {
+    BACKGROUNDS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Run `f` with [is_light] reporting `light`, while drawing to a terminal with that background.
+pub(crate) fn with_light<T>(light: bool, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<bool>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            DRAWING.set(self.0);
+        }
+    }
+
+    let _restore = Restore(DRAWING.replace(Some(light)));
+    f()
+}
+
+/// Returns whether the terminal background is light, which decides the color an
+/// [AdaptiveColor] resolves to.
+///
+/// While a [Terminal](crate::terminal::Terminal) is drawing, this is the terminal's
+/// [is_light](crate::terminal::Terminal::is_light). Otherwise it is the background queried by
+/// the most recently created terminal which is still alive. If no terminal knows its
+/// background, the `COLORFGBG` environment variable set by some terminals is used, and failing
+/// that the background is assumed to be dark.
 pub fn is_light() -> bool {
-    terminal_light::luma().map_or(false, |luma| luma > 0.6)
+    DRAWING
+        .get()
+        .or_else(|| backgrounds().last().map(|(_, light)| *light))
+        .unwrap_or_else(env_is_light)
+}
+
+/// Whether the `COLORFGBG` environment variable says the background is light.
+pub(crate) fn env_is_light() -> bool {
+    std::env::var("COLORFGBG").is_ok_and(|colors| {
+        // The background is the last entry, with 7 (white) and 9-15 (bright colors) as light
+        colors
+            .rsplit(';')
+            .next()
+            .and_then(|bg| bg.parse::<u8>().ok())
+            .is_some_and(|bg| bg == 7 || (9..=15).contains(&bg))
+    })
 }
 
 /// Trait for retrieving the style of a type as a reference
@@ -28,9 +28,16 @@ impl AdaptiveColor {
     }
 
     /// Get the applicable color based on the terminal background. Will return the dark color if
-    /// the background cannot be determined.
+    /// the background cannot be determined. See [is_light] for how the background is determined.
+    ///
+    /// Conversions through [From] use this too.
     pub fn get(&self) -> Color {
-        match is_light() {
+        self.resolve(is_light())
+    }
+
+    /// Get the light or dark color depending on whether the background is light.
+    pub const fn resolve(&self, light: bool) -> Color {
+        match light {
             true => self.light,
             false => self.dark,
         }
@@ -85,14 +92,14 @@ impl From<Color> for AdaptiveColor {
     }
 }
 
-impl Into<Color> for AdaptiveColor {
-    fn into(self) -> Color {
-        self.get()
+impl From<AdaptiveColor> for Color {
+    fn from(value: AdaptiveColor) -> Self {
+        value.get()
     }
 }
 
-impl Into<Style> for AdaptiveColor {
-    fn into(self) -> Style {
-        self.as_fg()
+impl From<AdaptiveColor> for Style {
+    fn from(value: AdaptiveColor) -> Self {
+        value.as_fg()
     }
 }
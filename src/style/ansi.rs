@@ -0,0 +1,269 @@
+//! Parsing of text written for a terminal, such as the output of a child process, into
+//! [StyledString]s.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::style::{Attribute, Color, Style, StyledString};
+
+/// The columns between tab stops.
+const TAB_WIDTH: usize = 8;
+
+/// Turns lines of text containing ANSI escape sequences into [StyledString]s.
+///
+/// SGR sequences which set colors and attributes are followed, while other escape sequences and
+/// control characters are dropped. The style carries over from one line to the next, as it would
+/// in a terminal. A carriage return followed by more text starts the line over, so only the last
+/// state of a progress bar redrawn in place is kept.
+///
+/// ```
+/// use tdrop::style::{AnsiParser, Color};
+///
+/// let mut parser = AnsiParser::new();
+/// let line = parser.parse("\x1b[31merror\x1b[0m: not found");
+/// assert_eq!(line[0].content(), "error");
+/// assert_eq!(line[0].style().fg, Some(Color::Red));
+/// assert_eq!(line[1].content(), ": not found");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct AnsiParser {
+    style: Style,
+}
+
+impl AnsiParser {
+    /// Create a parser starting with the default style.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a single line, without its line ending, into strings of consistent style.
+    pub fn parse(&mut self, line: &str) -> Vec<StyledString<String>> {
+        let mut strings: Vec<StyledString<String>> = vec![];
+        let mut text = String::new();
+        let mut column = 0;
+        let mut carriage_return = false;
+
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                let style = self.escape(&mut chars);
+                if style != self.style {
+                    flush(&mut strings, &mut text, self.style);
+                    self.style = style;
+                }
+                continue;
+            }
+            if c == '\r' {
+                carriage_return = true;
+                continue;
+            }
+            if c != '\t' && c.is_control() {
+                continue;
+            }
+
+            if carriage_return {
+                strings.clear();
+                text.clear();
+                column = 0;
+                carriage_return = false;
+            }
+            if c == '\t' {
+                let spaces = TAB_WIDTH - column % TAB_WIDTH;
+                text.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            } else {
+                text.push(c);
+                column += c.encode_utf8(&mut [0; 4]).width();
+            }
+        }
+
+        flush(&mut strings, &mut text, self.style);
+        strings
+    }
+
+    /// Consume an escape sequence after the ESC character, returning the style it results in.
+    fn escape(&self, chars: &mut Peekable<Chars>) -> Style {
+        match chars.next() {
+            // CSI, which ends with a byte in the range @ to ~
+            Some('[') => {
+                let mut params = String::new();
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        if c == 'm' {
+                            return sgr(self.style, &params);
+                        }
+                        break;
+                    }
+                    params.push(c);
+                }
+            }
+            // OSC, which ends with BEL or ST (ESC \)
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.style
+    }
+}
+
+/// Add the text collected so far as a string of the given style.
+fn flush(strings: &mut Vec<StyledString<String>>, text: &mut String, style: Style) {
+    if !text.is_empty() {
+        strings.push(StyledString::new(style, std::mem::take(text)));
+    }
+}
+
+/// Apply the parameters of an SGR sequence to a style.
+///
+/// Parameters are separated by `;`, and extended colors may either continue into the following
+/// parameters (`38;2;r;g;b`) or be given as a single parameter with `:` separated sub-parameters
+/// (`38:2::r:g:b`), where the empty one is an optional color space.
+fn sgr(mut style: Style, params: &str) -> Style {
+    let mut params = params.split(';');
+
+    while let Some(param) = params.next() {
+        let mut subparams = param.split(':');
+        let code = parse(subparams.next());
+        let grouped = param.contains(':');
+        let mut color = || {
+            if grouped {
+                extended_grouped(&mut subparams)
+            } else {
+                extended(&mut params.by_ref().map(|param| parse(Some(param))))
+            }
+        };
+
+        match code {
+            0 => style = Style::default(),
+            1 => style.attributes.set(Attribute::Bold),
+            2 => style.attributes.set(Attribute::Dim),
+            3 => style.attributes.set(Attribute::Italic),
+            4 => style.attributes.set(Attribute::Underlined),
+            5 => style.attributes.set(Attribute::SlowBlink),
+            6 => style.attributes.set(Attribute::RapidBlink),
+            7 => style.attributes.set(Attribute::Reverse),
+            8 => style.attributes.set(Attribute::Hidden),
+            9 => style.attributes.set(Attribute::Strikethrough),
+            21 | 22 => {
+                style.attributes.unset(Attribute::Bold);
+                style.attributes.unset(Attribute::Dim);
+            }
+            23 => style.attributes.unset(Attribute::Italic),
+            24 => style.attributes.unset(Attribute::Underlined),
+            25 => {
+                style.attributes.unset(Attribute::SlowBlink);
+                style.attributes.unset(Attribute::RapidBlink);
+            }
+            27 => style.attributes.unset(Attribute::Reverse),
+            28 => style.attributes.unset(Attribute::Hidden),
+            29 => style.attributes.unset(Attribute::Strikethrough),
+            30..=37 => style.fg = Some(Color::ansi((code - 30) as u8)),
+            38 => style.fg = color().or(style.fg),
+            39 => style.fg = None,
+            40..=47 => style.bg = Some(Color::ansi((code - 40) as u8)),
+            48 => style.bg = color().or(style.bg),
+            49 => style.bg = None,
+            58 => style.underline = color().or(style.underline),
+            59 => style.underline = None,
+            90..=97 => style.fg = Some(Color::ansi((code - 90 + 8) as u8)),
+            100..=107 => style.bg = Some(Color::ansi((code - 100 + 8) as u8)),
+            _ => {}
+        }
+    }
+    style
+}
+
+/// Parse a numeric parameter, where a missing or empty one is 0.
+fn parse(param: Option<&str>) -> u16 {
+    param.and_then(|param| param.parse().ok()).unwrap_or(0)
+}
+
+/// Parse the color of an extended color code (38, 48 or 58), which is either `5;n` for a color
+/// from the 256 color palette or `2;r;g;b`.
+fn extended(codes: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    let mut channel = || codes.next().and_then(|value| u8::try_from(value).ok());
+    match channel()? {
+        5 => channel().map(Color::Indexed),
+        2 => Some(Color::Rgb {
+            r: channel()?,
+            g: channel()?,
+            b: channel()?,
+        }),
+        _ => None,
+    }
+}
+
+/// Parse the sub-parameters of an extended color code given with colons, which are either `5:n`
+/// or `2:[color space]:r:g:b`. Some programs leave out the color space entirely, which is
+/// recognised by there being only three channels.
+fn extended_grouped<'a>(subparams: impl Iterator<Item = &'a str>) -> Option<Color> {
+    let values: Vec<u16> = subparams.map(|value| parse(Some(value))).collect();
+    let channel = |value: &u16| u8::try_from(*value).ok();
+    match values.as_slice() {
+        [5, index, ..] => channel(index).map(Color::Indexed),
+        [2, _, r, g, b, ..] | [2, r, g, b] => Some(Color::Rgb {
+            r: channel(r)?,
+            g: channel(g)?,
+            b: channel(b)?,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnsiParser;
+    use crate::style::{Attribute, Color, Style};
+
+    #[test]
+    fn parse_sgr() {
+        let mut parser = AnsiParser::new();
+        let line = parser.parse("\x1b[1;38;2;1;2;3mbold\x1b[22m plain\x1b[K");
+        assert_eq!(line.len(), 2);
+        assert_eq!(
+            *line[0].style(),
+            Style::new()
+                .fg(Color::Rgb { r: 1, g: 2, b: 3 })
+                .attribute(Attribute::Bold)
+        );
+        assert_eq!(line[1].content(), " plain");
+
+        // The color carries over to the next line
+        let line = parser.parse("\tnext");
+        assert_eq!(line[0].content(), "        next");
+        assert_eq!(line[0].style().fg, Some(Color::Rgb { r: 1, g: 2, b: 3 }));
+    }
+
+    #[test]
+    fn parse_colon_subparameters() {
+        let mut parser = AnsiParser::new();
+        let line = parser.parse("\x1b[38:2::1:2:3;1mrgb\x1b[48:5:4;38:2:4:5:6mindexed");
+        assert_eq!(
+            *line[0].style(),
+            Style::new()
+                .fg(Color::Rgb { r: 1, g: 2, b: 3 })
+                .attribute(Attribute::Bold)
+        );
+        assert_eq!(line[1].style().bg, Some(Color::Indexed(4)));
+        assert_eq!(line[1].style().fg, Some(Color::Rgb { r: 4, g: 5, b: 6 }));
+    }
+
+    #[test]
+    fn carriage_return_starts_over() {
+        let mut parser = AnsiParser::new();
+        let line = parser.parse("10%\r\x1b]0;title\x07100%\r");
+        assert_eq!(line.len(), 1);
+        assert_eq!(line[0].content(), "100%");
+    }
+}
@@ -61,9 +61,9 @@ pub enum Attribute {
     NoStrikethrough = 29,
 }
 
-impl Into<style::Attribute> for Attribute {
-    fn into(self) -> style::Attribute {
-        match self {
+impl From<Attribute> for style::Attribute {
+    fn from(value: Attribute) -> Self {
+        match value {
             Attribute::Reset => style::Attribute::Reset,
             Attribute::Bold => style::Attribute::Bold,
             Attribute::Dim => style::Attribute::Dim,
@@ -151,10 +151,7 @@ pub struct Attributes(u32);
 
 impl GetAttributes<Attribute> for Attributes {
     fn get_attributes(self) -> Vec<Attribute> {
-        Attribute::iterator()
-            .into_iter()
-            .filter(|a| self.has(*a))
-            .collect()
+        Attribute::iterator().filter(|a| self.has(*a)).collect()
     }
 }
 
@@ -174,9 +171,9 @@ impl From<style::Attributes> for Attributes {
     }
 }
 
-impl Into<style::Attributes> for Attributes {
-    fn into(self) -> style::Attributes {
-        let attrs = self.get_attributes();
+impl From<Attributes> for style::Attributes {
+    fn from(value: Attributes) -> Self {
+        let attrs = value.get_attributes();
         let mut ret = style::Attributes::none();
         attrs
             .iter()
@@ -282,9 +279,9 @@ impl From<u32> for Attributes {
     }
 }
 
-impl Into<u32> for Attributes {
-    fn into(self) -> u32 {
-        self.0
+impl From<Attributes> for u32 {
+    fn from(value: Attributes) -> Self {
+        value.0
     }
 }
 
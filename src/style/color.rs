@@ -55,3 +55,129 @@ pub enum Color {
     /// An ANSI color. See [256 colors - cheat sheet](https://jonasjacek.github.io/colors/) for more info.
     Indexed(u8),
 }
+
+/// The range of colors a terminal can display, used to convert colors it can't show into the
+/// closest ones it can.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorMode {
+    /// 24-bit RGB colors.
+    #[default]
+    TrueColor,
+    /// The 256 color palette.
+    Ansi256,
+    /// The 16 basic ANSI colors.
+    Ansi16,
+    /// No colors at all, e.g. when `NO_COLOR` is set.
+    NoColor,
+}
+
+/// The usual RGB values of the 16 basic ANSI colors, in palette order.
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (128, 0, 0)),
+    (Color::Green, (0, 128, 0)),
+    (Color::Yellow, (128, 128, 0)),
+    (Color::Blue, (0, 0, 128)),
+    (Color::Magenta, (128, 0, 128)),
+    (Color::Cyan, (0, 128, 128)),
+    (Color::Gray, (192, 192, 192)),
+    (Color::DarkGray, (128, 128, 128)),
+    (Color::BrightRed, (255, 0, 0)),
+    (Color::BrightGreen, (0, 255, 0)),
+    (Color::BrightYellow, (255, 255, 0)),
+    (Color::BrightBlue, (0, 0, 255)),
+    (Color::BrightMagenta, (255, 0, 255)),
+    (Color::BrightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// The levels of each channel in the 6x6x6 color cube of the 256 color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl Color {
+    /// One of the 16 basic colors by its index in the 256 color palette.
+    pub(crate) const fn ansi(index: u8) -> Self {
+        ANSI_COLORS[index as usize % 16].0
+    }
+
+    /// Convert the color to the closest one which can be displayed in the given [ColorMode].
+    pub fn downgrade(self, mode: ColorMode) -> Self {
+        match (mode, self) {
+            (_, Self::Reset) | (ColorMode::TrueColor, _) => self,
+            (ColorMode::NoColor, _) => Self::Reset,
+            (ColorMode::Ansi256, Self::Rgb { r, g, b }) => {
+                let level = |c: u8| {
+                    CUBE_LEVELS
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|(_, level)| level.abs_diff(c))
+                        .map_or(0, |(i, _)| i as u8)
+                };
+                Self::Indexed(16 + 36 * level(r) + 6 * level(g) + level(b))
+            }
+            (ColorMode::Ansi256, _) => self,
+            (ColorMode::Ansi16, Self::Indexed(index)) if index < 16 => {
+                ANSI_COLORS[usize::from(index)].0
+            }
+            (ColorMode::Ansi16, Self::Indexed(index)) => nearest_ansi(indexed_rgb(index)),
+            (ColorMode::Ansi16, Self::Rgb { r, g, b }) => nearest_ansi((r, g, b)),
+            (ColorMode::Ansi16, _) => self,
+        }
+    }
+}
+
+/// The RGB value of a color from the 256 color palette above the 16 basic colors.
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    if index >= 232 {
+        let gray = 8 + 10 * (index - 232);
+        return (gray, gray, gray);
+    }
+
+    let cube = index - 16;
+    (
+        CUBE_LEVELS[usize::from(cube / 36)],
+        CUBE_LEVELS[usize::from(cube / 6 % 6)],
+        CUBE_LEVELS[usize::from(cube % 6)],
+    )
+}
+
+/// The basic ANSI color closest to an RGB value.
+fn nearest_ansi((r, g, b): (u8, u8, u8)) -> Color {
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        u32::from(r.abs_diff(cr)).pow(2)
+            + u32::from(g.abs_diff(cg)).pow(2)
+            + u32::from(b.abs_diff(cb)).pow(2)
+    };
+
+    ANSI_COLORS
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Color, ColorMode};
+
+    #[test]
+    fn downgrade() {
+        let orange = Color::Rgb {
+            r: 255,
+            g: 135,
+            b: 0,
+        };
+
+        assert_eq!(orange.downgrade(ColorMode::TrueColor), orange);
+        assert_eq!(orange.downgrade(ColorMode::Ansi256), Color::Indexed(208));
+        assert_eq!(orange.downgrade(ColorMode::Ansi16), Color::BrightYellow);
+        assert_eq!(orange.downgrade(ColorMode::NoColor), Color::Reset);
+        assert_eq!(
+            Color::Indexed(9).downgrade(ColorMode::Ansi16),
+            Color::BrightRed
+        );
+        assert_eq!(
+            Color::Indexed(240).downgrade(ColorMode::Ansi16),
+            Color::DarkGray
+        );
+    }
+}
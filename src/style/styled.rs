@@ -1,5 +1,8 @@
 use std::fmt::Display;
 
+use crossterm::style::{ContentStyle, StyledContent};
+
+use crate::backend::IntoCrossterm;
 use crate::style::{AsStyle, AsStyleMut, Style};
 
 /// [StyledString] is a type associating a [Style] with any type which implements [Display].
@@ -39,6 +42,25 @@ impl<D: Display> StyledString<D> {
     pub fn len(&self) -> usize {
         self.content.to_string().len()
     }
+
+    /// Return whether the inner content of the [StyledString] is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<D: Display> Display for StyledString<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let style = ContentStyle {
+            foreground_color: self.style.fg.map(IntoCrossterm::into_crossterm),
+            background_color: self.style.bg.map(IntoCrossterm::into_crossterm),
+            underline_color: self.style.underline.map(IntoCrossterm::into_crossterm),
+            attributes: self.style.attributes.into(),
+        };
+
+        StyledContent::new(style, &self.content).fmt(f)
+    }
 }
 
 impl<D: Display> AsStyle for StyledString<D> {
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use crate::style::{style, AsStyle, AsStyleMut, Attribute, Color, Style, StyledString};
+use crate::style::{AsStyle, AsStyleMut, Attribute, Color, Style, StyledString, style};
 
 /// The [Stylize] trait is used to change the styles of both [Style](super::Style) and any
 /// given variable which implements [Display](std::fmt::Display).
@@ -1,10 +1,37 @@
 //! Implements the [Terminal] abstraction over output.
 
+mod builder;
+pub use builder::{Cleanup, Output, TerminalBuilder};
+
+mod capabilities;
+pub use capabilities::{Capabilities, PROBE_TIMEOUT};
+
+mod clipboard;
+
+mod command;
+
+mod colors;
+pub use colors::DefaultColors;
+
+mod handle;
+pub use handle::{RenderThread, TerminalHandle};
+
+mod region;
+pub use region::RegionId;
+pub(crate) use region::Regions;
+
+mod reply;
+
+mod run;
+pub use run::{Control, Outcome};
+
 mod viewport;
 pub use viewport::Viewport;
 
+#[allow(clippy::module_inception)]
 mod terminal;
-pub use terminal::Terminal;
+pub(crate) use terminal::used_height;
+pub use terminal::{Options, Terminal};
 
 mod frame;
-pub use frame::Frame;
+pub use frame::{CompletedFrame, Frame, RenderStats};
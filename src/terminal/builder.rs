@@ -0,0 +1,242 @@
+//! A builder for configuring a [Terminal] before it is created.
+
+use std::io::{self, IsTerminal, Write};
+
+use crate::{
+    backend::Backend,
+    style::ColorMode,
+    terminal::{Capabilities, DefaultColors, Options, Terminal, Viewport},
+    theme::Theme,
+};
+
+/// The stream a terminal created by [init_with](crate::init_with) writes to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Output {
+    /// Standard output
+    #[default]
+    Stdout,
+    /// Standard error, which leaves stdout free to be piped elsewhere
+    Stderr,
+}
+
+impl Output {
+    /// Whether the stream is connected to a terminal.
+    pub fn is_terminal(&self) -> bool {
+        match self {
+            Self::Stdout => io::stdout().is_terminal(),
+            Self::Stderr => io::stderr().is_terminal(),
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Stdout => io::stdout().write(buf),
+            Self::Stderr => io::stderr().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Stdout => io::stdout().flush(),
+            Self::Stderr => io::stderr().flush(),
+        }
+    }
+}
+
+/// What happens to the viewport when the [Terminal] is dropped.
+///
+/// Whichever is chosen, the cursor is shown and left at the start of a fresh line below what
+/// remains, with colors and attributes reset, so a shell prompt doesn't end up inside the last
+/// frame.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Cleanup {
+    /// Leave the last frame on screen
+    #[default]
+    Keep,
+    /// Clear the viewport so output continues where it started
+    Clear,
+    /// Replace the viewport with a single line of text, cut off at the width of the viewport
+    Summary(String),
+}
+
+/// Configures and creates a [Terminal].
+///
+/// Use [init_with](crate::init_with) to create a terminal writing to stdout or stderr, or
+/// [build](Self::build) to create one with any [Backend].
+///
+/// ```no_run
+/// use tdrop::terminal::{TerminalBuilder, Viewport};
+///
+/// let terminal = tdrop::init_with(TerminalBuilder::new().viewport(Viewport::Fit).frame_rate(60));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TerminalBuilder {
+    pub(crate) options: Options,
+    pub(crate) output: Output,
+}
+
+impl TerminalBuilder {
+    /// Create a builder with the default options: a one line [Inline](Viewport::Inline) viewport
+    /// on stdout which exits on CTRL+C.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the [Viewport] the terminal draws into.
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub const fn viewport(mut self, viewport: Viewport) -> Self {
+        self.options.viewport = viewport;
+        self
+    }
+
+    /// Set whether CTRL+C interrupts live components.
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub const fn respect_exit(mut self, respect_exit: bool) -> Self {
+        self.options.respect_exit = respect_exit;
+        self
+    }
+
+    /// Set the stream the terminal writes to. This is only used by [init_with](crate::init_with),
+    /// as [build](Self::build) writes to whatever the backend does.
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub const fn output(mut self, output: Output) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Set the [ColorMode] colors are converted to before they are drawn, instead of detecting it
+    /// from the terminal's [Capabilities] and the `NO_COLOR` environment variable.
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub const fn color_mode(mut self, color_mode: ColorMode) -> Self {
+        self.options.color_mode = Some(color_mode);
+        self
+    }
+
+    /// Set the frame rate live components are drawn at, see
+    /// [frame_rate](Terminal::frame_rate).
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub const fn frame_rate(mut self, frame_rate: u32) -> Self {
+        self.options.frame_rate = frame_rate;
+        self
+    }
+
+    /// Set the [Theme] available to components through
+    /// [Frame::theme](crate::terminal::Frame::theme).
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub const fn theme(mut self, theme: Theme) -> Self {
+        self.options.theme = theme;
+        self
+    }
+
+    /// Set what happens to the viewport when the terminal is dropped.
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub fn cleanup(mut self, cleanup: Cleanup) -> Self {
+        self.options.cleanup = cleanup;
+        self
+    }
+
+    /// Set whether raw mode is only entered while interactive components are active, see
+    /// [init_lazy](crate::init_lazy).
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub const fn lazy_raw_mode(mut self, lazy_raw_mode: bool) -> Self {
+        self.options.lazy_raw_mode = lazy_raw_mode;
+        self
+    }
+
+    /// Set whether CTRL+Z suspends the process while the terminal is in raw mode, see
+    /// [handle_event](Terminal::handle_event).
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub const fn suspend_on_ctrl_z(mut self, suspend: bool) -> Self {
+        self.options.suspend = suspend;
+        self
+    }
+
+    /// Set the features supported by the terminal. [init_with](crate::init_with) probes these
+    /// itself.
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub fn capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.options.capabilities = capabilities;
+        self
+    }
+
+    /// Set the default colors of the terminal. [init_with](crate::init_with) queries these
+    /// itself.
+    #[must_use = "moves the value of self and returns the modified value"]
+    pub const fn colors(mut self, colors: DefaultColors) -> Self {
+        self.options.colors = colors;
+        self
+    }
+
+    /// Create a [Terminal] with these options which draws through the given [Backend].
+    pub fn build<B: Backend>(self, backend: B) -> Result<Terminal<B>, B::Error> {
+        Terminal::with_options(backend, self.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cleanup, TerminalBuilder};
+    use crate::{
+        backend::TestBackend,
+        style::{AdaptiveColor, Color, ColorMode},
+        terminal::{DefaultColors, Viewport},
+    };
+
+    #[test]
+    fn build_with_options() {
+        let mut terminal = TerminalBuilder::new()
+            .viewport(Viewport::Inline(2))
+            .color_mode(ColorMode::NoColor)
+            .frame_rate(10)
+            .cleanup(Cleanup::Clear)
+            .build(TestBackend::new(10, 5))
+            .unwrap();
+        assert_eq!(terminal.color_mode(), ColorMode::NoColor);
+        assert_eq!(terminal.frame_rate(), 10);
+        assert_eq!(terminal.get_frame().area().height, 2);
+
+        terminal
+            .draw(|frame| frame.buffer[(0, 0)].set_symbol("x").fg = Color::Red)
+            .unwrap();
+        assert_eq!(terminal.backend().screen()[(0, 0)].fg, Color::Reset);
+    }
+
+    #[test]
+    fn background_is_per_terminal() {
+        let build = |background| {
+            TerminalBuilder::new()
+                .colors(DefaultColors {
+                    foreground: None,
+                    background: Some(background),
+                })
+                .build(TestBackend::new(10, 5))
+                .unwrap()
+        };
+        let mut light = build(Color::Rgb {
+            r: 250,
+            g: 250,
+            b: 250,
+        });
+        let mut dark = build(Color::Rgb { r: 0, g: 0, b: 0 });
+        assert!(light.is_light());
+        assert!(!dark.is_light());
+        assert!(!dark.get_frame().is_light());
+
+        // Adaptive colors resolve against the terminal they are drawn with
+        let color = AdaptiveColor::new(Color::Black, Color::White);
+        light
+            .draw(|frame| frame.buffer[(0, 0)].fg = color.into())
+            .unwrap();
+        dark.draw(|frame| frame.buffer[(0, 0)].fg = color.into())
+            .unwrap();
+        assert_eq!(light.backend().screen()[(0, 0)].fg, Color::Black);
+        assert_eq!(dark.backend().screen()[(0, 0)].fg, Color::White);
+
+        // Outside of drawing, the most recently created terminal which is still alive is used
+        assert_eq!(color.get(), Color::White);
+        drop(dark);
+        assert_eq!(Color::from(color), Color::Black);
+    }
+}
@@ -0,0 +1,288 @@
+//! Detection of the features supported by the terminal emulator.
+//!
+//! Capabilities are determined by sending a batch of queries (XTVERSION, DA1, DA2, DECRQM and
+//! the kitty protocol queries) and parsing whatever replies arrive, then filling any gaps from the
+//! environment.
+
+use std::{env, time::Duration};
+
+use crate::backend::Backend;
+use crate::layout::Size;
+use crate::style::ColorMode;
+use crate::terminal::reply::{Reply, replies};
+
+/// The default time to wait for the terminal to answer capability queries.
+pub const PROBE_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Queries sent while probing: XTVERSION, DA2, the cell size in pixels, DECRQM for synchronized
+/// output (mode 2026), the kitty keyboard protocol flags and a kitty graphics protocol support
+/// query. DA1 is appended by the [Backend] as a fence.
+const QUERIES: &str = concat!(
+    "\x1b[>0q",
+    "\x1b[>c",
+    "\x1b[16t",
+    "\x1b[?2026$p",
+    "\x1b[?u",
+    "\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\",
+);
+
+/// Terminals known to support truecolor, hyperlinks and styled underlines.
+const MODERN_TERMINALS: &[&str] = &[
+    "kitty", "wezterm", "foot", "ghostty", "iterm", "contour", "rio",
+];
+
+/// Terminals known to support truecolor in addition to [MODERN_TERMINALS].
+const TRUECOLOR_TERMINALS: &[&str] = &["alacritty", "konsole", "windows terminal", "vte", "vscode"];
+
+/// Terminals known to support OSC 8 hyperlinks in addition to [MODERN_TERMINALS].
+const HYPERLINK_TERMINALS: &[&str] = &["alacritty", "konsole", "windows terminal", "vte", "vscode"];
+
+/// Terminals known to support styled underlines in addition to [MODERN_TERMINALS].
+const UNDERLINE_TERMINALS: &[&str] = &["alacritty", "vte"];
+
+/// The features supported by the terminal emulator.
+///
+/// Capabilities are probed once by [try_init](crate::try_init) and can be retrieved with
+/// [Terminal::capabilities](crate::terminal::Terminal::capabilities). Anything which could not be
+/// determined is reported as unsupported.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+    /// The name of the terminal emulator, if known.
+    pub name: Option<String>,
+    /// The version of the terminal emulator, if known.
+    pub version: Option<String>,
+    /// Whether 24-bit RGB colors are supported.
+    pub truecolor: bool,
+    /// Whether synchronized output (mode 2026) is supported.
+    pub synchronized_output: bool,
+    /// Whether OSC 8 hyperlinks are supported.
+    pub hyperlinks: bool,
+    /// Whether the kitty keyboard protocol is supported.
+    pub kitty_keyboard: bool,
+    /// Whether the kitty graphics protocol is supported.
+    pub kitty_graphics: bool,
+    /// Whether sixel graphics are supported.
+    pub sixel: bool,
+    /// Whether styled (e.g. curly) and colored underlines are supported.
+    pub underline_styles: bool,
+    /// The size of a single cell in pixels, if known.
+    pub cell_size: Option<Size>,
+}
+
+impl Capabilities {
+    /// Probe the terminal through the given [Backend], waiting at most `timeout` for replies.
+    ///
+    /// The terminal must be in raw mode, otherwise replies won't be readable until the user
+    /// presses enter.
+    pub fn probe<B: Backend>(backend: &mut B, timeout: Duration) -> Result<Self, B::Error> {
+        let response = backend.query(QUERIES, timeout)?;
+        Ok(Self::detect(
+            response.as_deref().unwrap_or_default(),
+            |key| env::var(key).ok(),
+        ))
+    }
+
+    /// Determine capabilities from the environment alone, without querying the terminal.
+    pub fn from_env() -> Self {
+        Self::detect("", |key| env::var(key).ok())
+    }
+
+    /// The [ColorMode] to draw with: no colors if `NO_COLOR` is set, otherwise truecolor if
+    /// it is supported and the 256 color palette if not.
+    pub fn color_mode(&self) -> ColorMode {
+        if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            ColorMode::NoColor
+        } else if self.truecolor {
+            ColorMode::TrueColor
+        } else {
+            ColorMode::Ansi256
+        }
+    }
+
+    fn detect<E: Fn(&str) -> Option<String>>(response: &str, env: E) -> Self {
+        let mut capabilities = Self::default();
+        let mut firmware_version = None;
+
+        for reply in replies(response) {
+            match reply {
+                Reply::Dcs(body) => {
+                    if let Some(version) = body.strip_prefix(">|") {
+                        let (name, version) = parse_xtversion(version);
+                        capabilities.name = Some(name);
+                        capabilities.version = version;
+                    }
+                }
+                Reply::Apc(body) => {
+                    if body.starts_with('G') && body.ends_with(";OK") {
+                        capabilities.kitty_graphics = true;
+                    }
+                }
+                Reply::Csi(body) => {
+                    if let Some(params) = body.strip_prefix('?') {
+                        if let Some(attributes) = params.strip_suffix('c') {
+                            capabilities.sixel = attributes.split(';').any(|a| a == "4");
+                        } else if params.ends_with('u') {
+                            capabilities.kitty_keyboard = true;
+                        } else if let Some(mode) = params.strip_suffix("$y") {
+                            let mut mode = mode.split(';');
+                            if mode.next() == Some("2026") {
+                                capabilities.synchronized_output =
+                                    matches!(mode.next(), Some("1" | "2" | "3"));
+                            }
+                        }
+                    } else if let Some(params) =
+                        body.strip_prefix('>').and_then(|b| b.strip_suffix('c'))
+                    {
+                        firmware_version = params.split(';').nth(1).map(String::from);
+                    } else if let Some(params) =
+                        body.strip_prefix("6;").and_then(|b| b.strip_suffix('t'))
+                    {
+                        capabilities.cell_size = parse_cell_size(params);
+                    }
+                }
+                Reply::Osc(_) => {}
+            }
+        }
+
+        if capabilities.name.is_none() {
+            if let Some(program) = env("TERM_PROGRAM") {
+                capabilities.name = Some(program);
+                capabilities.version = env("TERM_PROGRAM_VERSION");
+            } else if env("WT_SESSION").is_some() {
+                capabilities.name = Some(String::from("Windows Terminal"));
+            } else if env("KITTY_WINDOW_ID").is_some() {
+                capabilities.name = Some(String::from("kitty"));
+            } else if let Some(version) = env("VTE_VERSION") {
+                capabilities.name = Some(String::from("VTE"));
+                capabilities.version = Some(version);
+            }
+        }
+        if capabilities.version.is_none() {
+            capabilities.version = firmware_version;
+        }
+
+        let name = capabilities
+            .name
+            .as_deref()
+            .unwrap_or_default()
+            .to_lowercase();
+        let vte = env("VTE_VERSION").is_some();
+        let known = |terminals: &[&str]| {
+            MODERN_TERMINALS
+                .iter()
+                .chain(terminals)
+                .any(|t| name.contains(t) || (vte && *t == "vte"))
+        };
+
+        capabilities.truecolor = matches!(env("COLORTERM").as_deref(), Some("truecolor" | "24bit"))
+            || known(TRUECOLOR_TERMINALS);
+        capabilities.hyperlinks = known(HYPERLINK_TERMINALS);
+        capabilities.underline_styles = known(UNDERLINE_TERMINALS);
+
+        capabilities
+    }
+}
+
+/// Parse the `height;width` parameters of a cell size report.
+fn parse_cell_size(params: &str) -> Option<Size> {
+    let (height, width) = params.split_once(';')?;
+    let size = Size {
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+    };
+
+    (size.width > 0 && size.height > 0).then_some(size)
+}
+
+/// Split an XTVERSION reply such as `kitty(0.31.0)` or `tmux 3.3a` into a name and version.
+fn parse_xtversion(reply: &str) -> (String, Option<String>) {
+    if let Some((name, version)) = reply.split_once('(') {
+        let version = version.trim_end_matches(')');
+        (String::from(name), Some(String::from(version)))
+    } else if let Some((name, version)) = reply.split_once(' ') {
+        (String::from(name), Some(String::from(version)))
+    } else {
+        (String::from(reply), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Capabilities;
+    use crate::layout::Size;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| String::from(*v))
+        }
+    }
+
+    #[test]
+    fn detect_kitty() {
+        let response = concat!(
+            "\x1bP>|kitty(0.31.0)\x1b\\",
+            "\x1b[>1;4000;29c",
+            "\x1b[6;20;10t",
+            "\x1b[?2026;2$y",
+            "\x1b[?0u",
+            "\x1b_Gi=31;OK\x1b\\",
+            "\x1b[?62;c",
+        );
+        let capabilities = Capabilities::detect(response, env(&[]));
+
+        assert_eq!(capabilities.name.as_deref(), Some("kitty"));
+        assert_eq!(capabilities.version.as_deref(), Some("0.31.0"));
+        assert!(capabilities.truecolor);
+        assert!(capabilities.synchronized_output);
+        assert!(capabilities.hyperlinks);
+        assert!(capabilities.kitty_keyboard);
+        assert!(capabilities.kitty_graphics);
+        assert!(capabilities.underline_styles);
+        assert!(!capabilities.sixel);
+        assert_eq!(
+            capabilities.cell_size,
+            Some(Size {
+                width: 10,
+                height: 20
+            })
+        );
+    }
+
+    #[test]
+    fn detect_xterm() {
+        let response = concat!(
+            "\x1bP>|XTerm(388)\x1b\\",
+            "\x1b[>41;388;0c",
+            "\x1b[?2026;0$y",
+            "\x1b[?63;1;2;4;6;9;15;16;22;28c",
+        );
+        let capabilities = Capabilities::detect(response, env(&[]));
+
+        assert_eq!(capabilities.name.as_deref(), Some("XTerm"));
+        assert_eq!(capabilities.version.as_deref(), Some("388"));
+        assert!(capabilities.sixel);
+        assert!(!capabilities.truecolor);
+        assert!(!capabilities.synchronized_output);
+        assert!(!capabilities.kitty_keyboard);
+    }
+
+    #[test]
+    fn detect_from_environment() {
+        let capabilities = Capabilities::detect(
+            "\x1b[>65;6800;1c\x1b[?65;1;9c",
+            env(&[("VTE_VERSION", "6800"), ("COLORTERM", "truecolor")]),
+        );
+
+        assert_eq!(capabilities.name.as_deref(), Some("VTE"));
+        assert_eq!(capabilities.version.as_deref(), Some("6800"));
+        assert!(capabilities.truecolor);
+        assert!(capabilities.hyperlinks);
+    }
+
+    #[test]
+    fn detect_without_reply() {
+        assert_eq!(Capabilities::detect("", env(&[])), Capabilities::default());
+    }
+}
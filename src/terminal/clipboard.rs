@@ -0,0 +1,93 @@
+//! Copying to the system clipboard through the terminal with OSC 52.
+//!
+//! OSC 52 asks the terminal emulator itself to set the clipboard, so it works over SSH where no
+//! local clipboard tool is available.
+
+use std::env;
+
+use crate::base64;
+
+/// The most bytes sent in a single passthrough to GNU screen, which truncates longer ones.
+const SCREEN_CHUNK_SIZE: usize = 512;
+
+/// Where the OSC 52 sequence has to be sent through to reach the terminal emulator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Multiplexer {
+    None,
+    Tmux,
+    Screen,
+}
+
+impl Multiplexer {
+    fn detect() -> Self {
+        if env::var_os("TMUX").is_some() {
+            Self::Tmux
+        } else if env::var("TERM").is_ok_and(|term| term.starts_with("screen")) {
+            Self::Screen
+        } else {
+            Self::None
+        }
+    }
+}
+
+/// Build the sequence which copies `text` to the clipboard, wrapped for passthrough if running
+/// inside tmux or GNU screen.
+pub(crate) fn copy_sequence(text: &str) -> String {
+    wrap(
+        &format!("\x1b]52;c;{}\x07", base64::encode(text.as_bytes())),
+        Multiplexer::detect(),
+    )
+}
+
+fn wrap(sequence: &str, multiplexer: Multiplexer) -> String {
+    match multiplexer {
+        Multiplexer::None => String::from(sequence),
+        // tmux requires escapes inside the passthrough to be doubled
+        Multiplexer::Tmux => format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b")),
+        // screen passes each chunk through as-is, so the terminal sees the whole sequence
+        Multiplexer::Screen => {
+            let mut wrapped = String::new();
+            let mut rest = sequence;
+            while !rest.is_empty() {
+                let mut end = rest.len().min(SCREEN_CHUNK_SIZE);
+                while !rest.is_char_boundary(end) {
+                    end -= 1;
+                }
+                wrapped.push_str("\x1bP");
+                wrapped.push_str(&rest[..end]);
+                wrapped.push_str("\x1b\\");
+                rest = &rest[end..];
+            }
+            wrapped
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Multiplexer, SCREEN_CHUNK_SIZE, wrap};
+
+    #[test]
+    fn wrap_tmux() {
+        assert_eq!(
+            wrap("\x1b]52;c;Zm9v\x07", Multiplexer::Tmux),
+            "\x1bPtmux;\x1b\x1b]52;c;Zm9v\x07\x1b\\"
+        );
+    }
+
+    #[test]
+    fn wrap_screen_in_chunks() {
+        let sequence = format!("\x1b]52;c;{}\x07", "A".repeat(2000));
+        let wrapped = wrap(&sequence, Multiplexer::Screen);
+
+        let chunks: Vec<&str> = wrapped
+            .strip_prefix("\x1bP")
+            .and_then(|wrapped| wrapped.strip_suffix("\x1b\\"))
+            .unwrap()
+            .split("\x1b\\\x1bP")
+            .collect();
+        assert_eq!(chunks.len(), 4);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= SCREEN_CHUNK_SIZE));
+        assert_eq!(chunks.concat(), sequence);
+    }
+}
@@ -0,0 +1,129 @@
+//! Detection of the terminal's default foreground and background colors.
+
+use std::time::Duration;
+
+use crate::{
+    backend::Backend,
+    style::Color,
+    terminal::reply::{Reply, replies},
+};
+
+/// OSC 10 and OSC 11 queries for the default foreground and background colors.
+const QUERIES: &str = "\x1b]10;?\x07\x1b]11;?\x07";
+
+/// The default foreground and background colors of the terminal.
+///
+/// Colors are queried once by [try_init](crate::try_init) and cached on the
+/// [Terminal](crate::terminal::Terminal). Either color is [None] if the terminal didn't answer
+/// in time or doesn't support the query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DefaultColors {
+    /// The default foreground color, if known.
+    pub foreground: Option<Color>,
+    /// The default background color, if known.
+    pub background: Option<Color>,
+}
+
+impl DefaultColors {
+    /// Query the default colors through the given [Backend], waiting at most `timeout` for
+    /// replies.
+    ///
+    /// The terminal must be in raw mode, otherwise replies won't be readable until the user
+    /// presses enter.
+    pub fn query<B: Backend>(backend: &mut B, timeout: Duration) -> Result<Self, B::Error> {
+        let response = backend.query(QUERIES, timeout)?;
+        Ok(Self::parse(response.as_deref().unwrap_or_default()))
+    }
+
+    /// Whether the background is light, or [None] if it is unknown.
+    pub fn is_light(&self) -> Option<bool> {
+        match self.background? {
+            Color::Rgb { r, g, b } => {
+                let luma = 0.2126 * f32::from(r) + 0.7152 * f32::from(g) + 0.0722 * f32::from(b);
+                Some(luma / 255.0 > 0.6)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse(response: &str) -> Self {
+        let mut colors = Self::default();
+
+        for reply in replies(response) {
+            let Reply::Osc(body) = reply else {
+                continue;
+            };
+            let Some((code, spec)) = body.split_once(';') else {
+                continue;
+            };
+
+            match code {
+                "10" => colors.foreground = parse_color_spec(spec),
+                "11" => colors.background = parse_color_spec(spec),
+                _ => {}
+            }
+        }
+
+        colors
+    }
+}
+
+/// Parse an X11 color specification such as `rgb:ffff/8080/0000` into an RGB [Color].
+fn parse_color_spec(spec: &str) -> Option<Color> {
+    let channels = spec
+        .strip_prefix("rgb:")
+        .or_else(|| spec.strip_prefix("rgba:"))?;
+    let mut channels = channels.split('/').map(|channel| {
+        let digits = u32::try_from(channel.len())
+            .ok()
+            .filter(|d| (1..=4).contains(d))?;
+        let value = u32::from_str_radix(channel, 16).ok()?;
+        let max = 16u32.pow(digits) - 1;
+        u8::try_from(value * 255 / max).ok()
+    });
+
+    Some(Color::Rgb {
+        r: channels.next()??,
+        g: channels.next()??,
+        b: channels.next()??,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DefaultColors;
+    use crate::style::Color;
+
+    #[test]
+    fn parse_colors() {
+        let colors = DefaultColors::parse(
+            "\x1b]10;rgb:ffff/ffff/ffff\x07\x1b]11;rgb:28/2c/34\x1b\\\x1b[?62c",
+        );
+
+        assert_eq!(
+            colors.foreground,
+            Some(Color::Rgb {
+                r: 255,
+                g: 255,
+                b: 255
+            })
+        );
+        assert_eq!(
+            colors.background,
+            Some(Color::Rgb {
+                r: 40,
+                g: 44,
+                b: 52
+            })
+        );
+        assert_eq!(colors.is_light(), Some(false));
+    }
+
+    #[test]
+    fn parse_unanswered() {
+        let colors = DefaultColors::parse("\x1b[?62c");
+
+        assert_eq!(colors, DefaultColors::default());
+        assert_eq!(colors.is_light(), None);
+    }
+}
@@ -0,0 +1,170 @@
+//! Running a child process with its output tailed in the viewport, see [Terminal::run_command].
+
+use std::io::{self, BufRead, BufReader, Read};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Sender, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{
+    backend::Backend,
+    component::tail::{Tail, TailState},
+    style::Style,
+    terminal::Terminal,
+};
+
+impl<B: Backend> Terminal<B>
+where
+    B::Error: From<io::Error>,
+{
+    /// Run a command, showing the last `height` lines of its output in a region of the viewport
+    /// while it runs.
+    ///
+    /// Both stdout and stderr are captured and colors in the output are kept. Once the command
+    /// exits, the region is replaced by a single line saying whether it succeeded. If it failed,
+    /// its full output is printed above that line so it stays in the scrollback. Pressing CTRL+C
+    /// kills the command if the terminal respects it.
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    ///
+    /// let mut terminal = tdrop::init();
+    /// let status = terminal.run_command(Command::new("cargo").arg("build"), 5)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn run_command(
+        &mut self,
+        command: &mut Command,
+        height: u16,
+    ) -> Result<ExitStatus, B::Error> {
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let (sender, receiver) = mpsc::channel();
+        if let Some(stdout) = child.stdout.take() {
+            forward_lines(stdout, sender.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            forward_lines(stderr, sender);
+        }
+
+        self.enter_interactive()?;
+        let region = self.add_region(height)?;
+        let frame_time = Duration::from_secs(1) / self.frame_rate().max(1);
+        let mut state = TailState::new();
+
+        // Output is drawn once per frame until both streams are closed
+        let mut open = true;
+        while open {
+            let next_frame = Instant::now() + frame_time;
+            while let Some((event, should_exit)) =
+                self.poll_event_timeout(next_frame.saturating_duration_since(Instant::now()))
+            {
+                if should_exit {
+                    // The command may already have exited, in which case there is nothing to kill
+                    let _ = child.kill();
+                }
+                self.handle_event(&event)?;
+            }
+
+            loop {
+                match receiver.try_recv() {
+                    Ok(line) => state.push(&line),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        open = false;
+                        break;
+                    }
+                }
+            }
+
+            self.draw(|frame| {
+                if let Some(area) = frame.region(region) {
+                    frame.render_stateful_component(Tail::new(height), area, &mut state);
+                }
+            })?;
+        }
+        let status = child.wait()?;
+
+        self.remove_region(region)?;
+
+        let program = command.get_program().to_string_lossy();
+        let args = command.get_args().map(|arg| arg.to_string_lossy());
+        let description = [program]
+            .into_iter()
+            .chain(args)
+            .collect::<Vec<_>>()
+            .join(" ");
+        if status.success() {
+            let style = Style::new().fg(self.theme().success.resolve(self.is_light()));
+            self.println(style.apply(format!("✓ {description}")))?;
+        } else {
+            for line in state.lines() {
+                self.println_styled(line.iter().cloned())?;
+            }
+            let style = Style::new().fg(self.theme().error.resolve(self.is_light()));
+            self.println(style.apply(format!("✗ {description} ({status})")))?;
+        }
+
+        self.leave_interactive()?;
+        Ok(status)
+    }
+}
+
+/// Send each line read from a stream to the channel from a new thread, until the stream is
+/// closed.
+fn forward_lines<R: Read + Send + 'static>(reader: R, sender: Sender<String>) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut line = vec![];
+        while reader
+            .read_until(b'\n', &mut line)
+            .is_ok_and(|read| read > 0)
+        {
+            let text = String::from_utf8_lossy(&line);
+            if sender
+                .send(text.trim_end_matches('\n').to_string())
+                .is_err()
+            {
+                return;
+            }
+            line.clear();
+        }
+    });
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::process::Command;
+
+    use crate::{backend::TestBackend, terminal::TerminalBuilder};
+
+    #[test]
+    fn collapses_into_result_line() {
+        let mut terminal = TerminalBuilder::new()
+            .frame_rate(1000)
+            .build(TestBackend::new(60, 6))
+            .unwrap();
+
+        let status = terminal
+            .run_command(Command::new("sh").args(["-c", "echo one; echo two"]), 3)
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(
+            terminal.backend().screen_lines()[..2],
+            ["✓ sh -c echo one; echo two", ""]
+        );
+
+        let status = terminal
+            .run_command(Command::new("sh").args(["-c", "echo oops >&2; exit 3"]), 3)
+            .unwrap();
+        assert!(!status.success());
+        assert_eq!(
+            terminal.backend().screen_lines()[1..3],
+            ["oops", "✗ sh -c echo oops >&2; exit 3 (exit status: 3)"]
+        );
+    }
+}
@@ -1,7 +1,11 @@
+use std::time::Duration;
+
 use crate::{
     buffer::Buffer,
     component::{Component, StatefulComponent},
     layout::{Position, Rect},
+    terminal::{Capabilities, RegionId, Regions},
+    theme::Theme,
 };
 
 #[derive(Debug, Hash)]
@@ -11,12 +15,52 @@ pub struct Frame<'a> {
     pub(crate) viewport_area: Rect,
 
     pub(crate) buffer: &'a mut Buffer,
+
+    pub(crate) capabilities: &'a Capabilities,
+
+    pub(crate) regions: &'a Regions,
+
+    pub(crate) theme: &'a Theme,
+
+    pub(crate) light: bool,
+
+    /// The number of rows used by components which reported their height
+    pub(crate) content_height: Option<u16>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct CompletedFrame<'a> {
     pub buffer: &'a Buffer,
     pub area: Rect,
+    /// What it took to draw the frame
+    pub stats: RenderStats,
+}
+
+/// Statistics about a single [draw](crate::terminal::Terminal::draw), for finding expensive
+/// components and keeping an eye on how much is sent to the terminal.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct RenderStats {
+    /// The number of cells which differed from the previous frame and were drawn
+    pub changed_cells: usize,
+    /// The number of bytes written to the terminal, if the [Backend](crate::backend::Backend)
+    /// counts them
+    pub bytes_written: u64,
+    /// The time spent rendering components into the buffer
+    pub render_time: Duration,
+    /// The time spent drawing the changes and flushing them to the terminal
+    pub flush_time: Duration,
+    /// The time since the start of the previous draw, or [None] for the first one
+    pub frame_time: Option<Duration>,
+}
+
+impl RenderStats {
+    /// The effective frame rate based on the time since the previous draw, or [None] for the
+    /// first one.
+    pub fn frame_rate(&self) -> Option<f64> {
+        self.frame_time
+            .filter(|time| !time.is_zero())
+            .map(|time| 1.0 / time.as_secs_f64())
+    }
 }
 
 impl Frame<'_> {
@@ -24,7 +68,39 @@ impl Frame<'_> {
         self.viewport_area
     }
 
+    /// The features supported by the terminal being rendered to
+    pub const fn capabilities(&self) -> &Capabilities {
+        self.capabilities
+    }
+
+    /// The [Theme] set with [TerminalBuilder::theme](crate::terminal::TerminalBuilder::theme)
+    pub const fn theme(&self) -> &Theme {
+        self.theme
+    }
+
+    /// Whether the terminal background is light, see
+    /// [Terminal::is_light](crate::terminal::Terminal::is_light)
+    pub const fn is_light(&self) -> bool {
+        self.light
+    }
+
+    /// The area of a region added with
+    /// [Terminal::add_region](crate::terminal::Terminal::add_region), or [None] if it was removed.
+    pub fn region(&self, id: RegionId) -> Option<Rect> {
+        self.regions.area(id, self.viewport_area)
+    }
+
+    /// Set the number of rows from the top of the viewport which are in use, overriding the
+    /// heights reported by components. This sizes [Viewport::Fit](crate::terminal::Viewport::Fit)
+    /// viewports and is ignored by others.
+    pub const fn set_content_height(&mut self, height: u16) {
+        self.content_height = Some(height);
+    }
+
     pub fn render_component<C: Component>(&mut self, component: C, area: Rect) {
+        if let Some(height) = component.height(area.width) {
+            self.use_rows(area, height);
+        }
         component.render(area, self.buffer);
     }
 
@@ -32,6 +108,16 @@ impl Frame<'_> {
     where
         C: StatefulComponent,
     {
+        if let Some(height) = component.height(area.width, state) {
+            self.use_rows(area, height);
+        }
         component.render(area, self.buffer, state)
     }
+
+    /// Record that a component uses `height` rows from the top of `area`.
+    fn use_rows(&mut self, area: Rect, height: u16) {
+        let bottom =
+            (area.top() + height.min(area.height)).saturating_sub(self.viewport_area.top());
+        self.content_height = Some(self.content_height.map_or(bottom, |used| used.max(bottom)));
+    }
 }
@@ -0,0 +1,269 @@
+//! Drawing from many threads through a [TerminalHandle] and a background render thread.
+
+use std::fmt::Display;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{
+    backend::Backend,
+    style::StyledString,
+    terminal::{Frame, Terminal},
+};
+
+type Update<S> = Box<dyn FnOnce(&mut S) + Send>;
+
+/// What the render thread returns once stopped.
+type Finished<B, S> = Result<(Terminal<B>, S), <B as Backend>::Error>;
+
+enum Message<S> {
+    Update(Update<S>),
+    Print(StyledString<String>),
+    Stop,
+}
+
+/// A cloneable handle for updating the state drawn by a render thread and printing above its
+/// viewport, created by [Terminal::spawn].
+///
+/// Everything sent through a handle is a request to the render thread, so none of the methods
+/// block on drawing. Requests sent after the render thread has stopped are ignored.
+pub struct TerminalHandle<S> {
+    sender: Sender<Message<S>>,
+}
+
+impl<S> Clone for TerminalHandle<S> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<S> TerminalHandle<S> {
+    /// Update the drawn state. Updates which arrive within the same frame are applied together
+    /// and drawn once.
+    pub fn update<F>(&self, update: F)
+    where
+        F: FnOnce(&mut S) + Send + 'static,
+    {
+        let _ = self.sender.send(Message::Update(Box::new(update)));
+    }
+
+    /// Print styled text above the viewport. See [Terminal::print].
+    pub fn print<D: Display>(&self, text: StyledString<D>) {
+        let text = StyledString::new(*text.style(), text.content().to_string());
+        let _ = self.sender.send(Message::Print(text));
+    }
+
+    /// Print styled text followed by a newline above the viewport. See [Terminal::println].
+    pub fn println<D: Display>(&self, text: StyledString<D>) {
+        let text = StyledString::new(*text.style(), format!("{}\n", text.content()));
+        let _ = self.sender.send(Message::Print(text));
+    }
+}
+
+/// The background thread drawing for a [TerminalHandle], created by [Terminal::spawn].
+pub struct RenderThread<B: Backend, S> {
+    sender: Sender<Message<S>>,
+    thread: JoinHandle<Finished<B, S>>,
+}
+
+impl<B: Backend, S> RenderThread<B, S> {
+    /// Whether the render thread has stopped on its own, because the user pressed CTRL+C and the
+    /// terminal is configured to respect it. [join](Self::join) returns right away once it has.
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Draw the final state, stop the render thread and return the terminal and state.
+    ///
+    /// Requests sent before this is called are handled first. If the render thread panicked, the
+    /// panic is resumed on the current thread.
+    pub fn join(self) -> Result<(Terminal<B>, S), B::Error> {
+        let _ = self.sender.send(Message::Stop);
+        self.thread
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+impl<B> Terminal<B>
+where
+    B: Backend + Send + 'static,
+    B::Error: Send,
+{
+    /// Move the terminal to a background thread which draws `state` with `render` at most `fps`
+    /// times a second, returning a [TerminalHandle] for other threads to update the state and
+    /// print through.
+    ///
+    /// The render thread draws once per frame and handles events from the terminal in between, such
+    /// as resizes. Updates and prints which arrive within the same frame are coalesced, with prints
+    /// going above the viewport before the frame is drawn. If the user presses CTRL+C and the
+    /// terminal respects it, the render thread stops, see [RenderThread::is_finished].
+    pub fn spawn<S, F>(
+        self,
+        state: S,
+        fps: u32,
+        render: F,
+    ) -> (TerminalHandle<S>, RenderThread<B, S>)
+    where
+        S: Send + 'static,
+        F: FnMut(&mut Frame, &S) + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let frame_time = Duration::from_secs(1) / fps.max(1);
+        let thread = thread::spawn(move || render_loop(self, state, frame_time, render, receiver));
+
+        (
+            TerminalHandle {
+                sender: sender.clone(),
+            },
+            RenderThread { sender, thread },
+        )
+    }
+}
+
+fn render_loop<B, S, F>(
+    mut terminal: Terminal<B>,
+    mut state: S,
+    frame_time: Duration,
+    mut render: F,
+    receiver: Receiver<Message<S>>,
+) -> Finished<B, S>
+where
+    B: Backend,
+    F: FnMut(&mut Frame, &S),
+{
+    terminal.enter_interactive()?;
+    let mut prints = vec![];
+
+    let mut stop = false;
+    while !stop {
+        let next_frame = Instant::now() + frame_time;
+        while let Some((event, should_exit)) =
+            terminal.poll_event_timeout(next_frame.saturating_duration_since(Instant::now()))
+        {
+            if should_exit {
+                terminal.leave_interactive()?;
+                return Ok((terminal, state));
+            }
+            terminal.handle_event(&event)?;
+        }
+        // Backends may return before the timeout once there are no more events
+        thread::sleep(next_frame.saturating_duration_since(Instant::now()));
+
+        loop {
+            match receiver.try_recv() {
+                Ok(Message::Update(update)) => update(&mut state),
+                Ok(Message::Print(text)) => prints.push(text),
+                Ok(Message::Stop) | Err(TryRecvError::Disconnected) => {
+                    stop = true;
+                    break;
+                }
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+
+        if !prints.is_empty() {
+            terminal.print_styled(prints.drain(..))?;
+        }
+        terminal.draw(|frame| render(frame, &state))?;
+    }
+
+    terminal.leave_interactive()?;
+    Ok((terminal, state))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    };
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::{
+        backend::TestBackend,
+        style::Stylize,
+        terminal::{Frame, TerminalBuilder},
+    };
+
+    fn render_count(frame: &mut Frame, count: &u16) {
+        let area = frame.area();
+        frame.buffer[(area.x, area.y)].set_symbol(&count.to_string());
+    }
+
+    #[test]
+    fn handles_update_and_print_from_threads() {
+        let terminal = TerminalBuilder::new()
+            .build(TestBackend::new(10, 5))
+            .unwrap();
+
+        let (handle, render_thread) = terminal.spawn(0, 60, render_count);
+
+        let workers: Vec<_> = (0..3)
+            .map(|_| {
+                let handle = handle.clone();
+                thread::spawn(move || handle.update(|count| *count += 1))
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        handle.print("a".stylize());
+        handle.println("b".stylize());
+
+        let (terminal, count) = render_thread.join().unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(terminal.backend().screen_lines(), ["ab", "3", "", "", ""]);
+    }
+
+    #[test]
+    fn draws_without_messages() {
+        let terminal = TerminalBuilder::new()
+            .build(TestBackend::new(10, 3))
+            .unwrap();
+
+        let drawn = Arc::new(AtomicBool::new(false));
+        let (_handle, render_thread) = terminal.spawn(7, 1000, {
+            let drawn = Arc::clone(&drawn);
+            move |frame, count| {
+                render_count(frame, count);
+                drawn.store(true, Ordering::Relaxed);
+            }
+        });
+
+        // Frames are drawn before anything is sent to the render thread
+        let start = Instant::now();
+        while !drawn.load(Ordering::Relaxed) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(1));
+        }
+        let (terminal, _) = render_thread.join().unwrap();
+        assert_eq!(terminal.backend().screen_lines(), ["7", "", ""]);
+    }
+
+    #[test]
+    fn ctrl_c_ends_the_loop() {
+        let mut backend = TestBackend::new(10, 3);
+        backend.push_event(Event::Key(KeyEvent::new(
+            KeyCode::Char('c'),
+            KeyModifiers::CONTROL,
+        )));
+        let terminal = TerminalBuilder::new().build(backend).unwrap();
+
+        // The handle is kept alive, so only CTRL+C can stop the render thread
+        let (handle, render_thread) = terminal.spawn(0, 1000, render_count);
+        let start = Instant::now();
+        while !render_thread.is_finished() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(1));
+        }
+        handle.update(|count| *count += 1);
+        let (_, count) = render_thread.join().unwrap();
+        assert_eq!(count, 0);
+    }
+}
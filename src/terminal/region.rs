@@ -0,0 +1,92 @@
+use crate::layout::Rect;
+
+/// Identifies a region of the viewport added with
+/// [Terminal::add_region](crate::terminal::Terminal::add_region).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegionId(usize);
+
+/// Slices of the viewport owned by independent live components, stacked from the top.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub(crate) struct Regions {
+    regions: Vec<(RegionId, u16)>,
+    next: usize,
+}
+
+impl Regions {
+    /// Add a region of the given height below the existing ones.
+    pub(crate) fn add(&mut self, height: u16) -> RegionId {
+        let id = RegionId(self.next);
+        self.next += 1;
+        self.regions.push((id, height));
+        id
+    }
+
+    /// Remove a region, returning whether it existed.
+    pub(crate) fn remove(&mut self, id: RegionId) -> bool {
+        let len = self.regions.len();
+        self.regions.retain(|(region, _)| *region != id);
+        self.regions.len() != len
+    }
+
+    /// The combined height of all regions.
+    pub(crate) fn height(&self) -> u16 {
+        self.regions
+            .iter()
+            .fold(0, |total, (_, height)| total.saturating_add(*height))
+    }
+
+    /// The area of a region within the viewport, clipped to the viewport.
+    pub(crate) fn area(&self, id: RegionId, viewport: Rect) -> Option<Rect> {
+        let mut y = viewport.top();
+        for (region, height) in &self.regions {
+            if *region == id {
+                let bottom = y.saturating_add(*height).min(viewport.bottom());
+                return Some(Rect {
+                    y: y.min(bottom),
+                    height: bottom.saturating_sub(y),
+                    ..viewport
+                });
+            }
+            y = y.saturating_add(*height);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Regions;
+    use crate::layout::Rect;
+
+    #[test]
+    fn regions_stack_from_the_top() {
+        let viewport = Rect {
+            x: 0,
+            y: 2,
+            width: 10,
+            height: 4,
+        };
+        let mut regions = Regions::default();
+        let first = regions.add(1);
+        let second = regions.add(2);
+        let third = regions.add(3);
+
+        assert_eq!(regions.height(), 6);
+        assert_eq!(
+            regions.area(second, viewport).map(|r| (r.y, r.height)),
+            Some((3, 2))
+        );
+        assert_eq!(
+            regions.area(third, viewport).map(|r| (r.y, r.height)),
+            Some((5, 1))
+        );
+
+        assert!(regions.remove(first));
+        assert!(!regions.remove(first));
+        assert_eq!(
+            regions.area(second, viewport).map(|r| (r.y, r.height)),
+            Some((2, 2))
+        );
+        assert_eq!(regions.area(first, viewport), None);
+    }
+}
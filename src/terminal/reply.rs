@@ -0,0 +1,78 @@
+//! Tokenizing of the sequences terminals send in reply to queries.
+
+/// A sequence sent by the terminal in reply to a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Reply<'a> {
+    /// A control sequence, excluding the `CSI` introducer but including the final byte.
+    Csi(&'a str),
+    /// A device control string, excluding the introducer and terminator.
+    Dcs(&'a str),
+    /// An application program command, excluding the introducer and terminator.
+    Apc(&'a str),
+    /// An operating system command, excluding the introducer and terminator.
+    Osc(&'a str),
+}
+
+/// Split a terminal response into the reply sequences it contains.
+pub(crate) fn replies(response: &str) -> Vec<Reply<'_>> {
+    let mut replies = vec![];
+    let mut rest = response;
+
+    while let Some(start) = rest.find('\x1b') {
+        rest = &rest[start + 1..];
+
+        let Some(introducer) = rest.chars().next() else {
+            break;
+        };
+        let body = &rest[introducer.len_utf8()..];
+
+        match introducer {
+            '[' => {
+                let Some(end) = body.find(|c: char| ('@'..='~').contains(&c)) else {
+                    break;
+                };
+                replies.push(Reply::Csi(&body[..=end]));
+                rest = &body[end + 1..];
+            }
+            'P' | '_' | ']' => {
+                let Some((content, remainder)) = split_string(body) else {
+                    break;
+                };
+                replies.push(match introducer {
+                    'P' => Reply::Dcs(content),
+                    '_' => Reply::Apc(content),
+                    _ => Reply::Osc(content),
+                });
+                rest = remainder;
+            }
+            _ => {}
+        }
+    }
+
+    replies
+}
+
+/// Split the body of a string sequence at its terminator (`ST` or `BEL`).
+fn split_string(body: &str) -> Option<(&str, &str)> {
+    let end = body.find(['\x1b', '\x07'])?;
+    let terminator = if body[end..].starts_with("\x1b\\") {
+        2
+    } else {
+        1
+    };
+
+    Some((&body[..end], &body[end + terminator..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Reply, replies};
+
+    #[test]
+    fn skips_unknown_introducers() {
+        assert_eq!(
+            replies("\x1bé\x1b[?62;4c\x1b]11;rgb:0/0/0\x1b\\"),
+            [Reply::Csi("?62;4c"), Reply::Osc("11;rgb:0/0/0")]
+        );
+    }
+}
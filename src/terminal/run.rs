@@ -0,0 +1,174 @@
+//! A render loop for stateful components, see [Terminal::run_stateful].
+
+use std::time::{Duration, Instant};
+
+use crate::{backend::Backend, component::StatefulComponent, terminal::Terminal};
+
+/// Returned by the event handler of [Terminal::run_stateful] to say whether to keep running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Control {
+    /// Keep rendering and handling events.
+    Continue,
+    /// Draw the final state and return.
+    Exit,
+}
+
+/// How [Terminal::run_stateful] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// The event handler returned [Control::Exit].
+    Finished,
+    /// The user pressed CTRL+C and the terminal is configured to respect it.
+    Interrupted,
+}
+
+impl<B: Backend> Terminal<B> {
+    /// Render a stateful component in the viewport until `handle` returns [Control::Exit].
+    ///
+    /// `component` creates the component for each frame, which is drawn at most `fps` times a
+    /// second. `handle` is called with each event as it arrives and with [None] once per frame,
+    /// so it can update the state for things like animations. Resizes are handled before
+    /// `handle` sees them. If CTRL+C is pressed and the terminal respects it, this returns
+    /// [Outcome::Interrupted] without calling `handle`.
+    pub fn run_stateful<C, F, H>(
+        &mut self,
+        component: F,
+        state: &mut C::State,
+        fps: u32,
+        handle: H,
+    ) -> Result<Outcome, B::Error>
+    where
+        C: StatefulComponent,
+        F: FnMut() -> C,
+        H: FnMut(&mut C::State, Option<B::Event>) -> Control,
+    {
+        self.enter_interactive()?;
+        let outcome = self.render_loop(component, state, fps, handle);
+        self.leave_interactive()?;
+        outcome
+    }
+
+    fn render_loop<C, F, H>(
+        &mut self,
+        mut component: F,
+        state: &mut C::State,
+        fps: u32,
+        mut handle: H,
+    ) -> Result<Outcome, B::Error>
+    where
+        C: StatefulComponent,
+        F: FnMut() -> C,
+        H: FnMut(&mut C::State, Option<B::Event>) -> Control,
+    {
+        let frame_time = Duration::from_secs(1) / fps.max(1);
+
+        loop {
+            self.draw(|frame| frame.render_stateful_component(component(), frame.area(), state))?;
+
+            let mut control = Control::Continue;
+            let next_frame = Instant::now() + frame_time;
+            while control == Control::Continue {
+                let remaining = next_frame.saturating_duration_since(Instant::now());
+                let Some((event, should_exit)) = self.poll_event_timeout(remaining) else {
+                    break;
+                };
+                if should_exit {
+                    return Ok(Outcome::Interrupted);
+                }
+                self.handle_event(&event)?;
+                control = handle(state, Some(event));
+            }
+
+            if control == Control::Continue {
+                control = handle(state, None);
+            }
+            if control == Control::Exit {
+                self.draw(|frame| {
+                    frame.render_stateful_component(component(), frame.area(), state)
+                })?;
+                return Ok(Outcome::Finished);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{Control, Outcome};
+    use crate::{
+        backend::TestBackend,
+        buffer::Buffer,
+        component::StatefulComponent,
+        layout::Rect,
+        terminal::{Terminal, TerminalBuilder},
+    };
+
+    struct Counter;
+
+    impl StatefulComponent for Counter {
+        type State = u32;
+
+        fn render(self, area: Rect, buffer: &mut Buffer, state: &mut Self::State) {
+            buffer[(area.x, area.y)].set_symbol(&state.to_string());
+        }
+    }
+
+    fn terminal(events: &[Event]) -> Terminal<TestBackend> {
+        let mut backend = TestBackend::new(10, 3);
+        for event in events {
+            backend.push_event(event.clone());
+        }
+        TerminalBuilder::new().build(backend).unwrap()
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    fn count_until_q(state: &mut u32, event: Option<Event>) -> Control {
+        match event {
+            Some(Event::Key(key)) if key.code == KeyCode::Char('q') => Control::Exit,
+            Some(_) => {
+                *state += 1;
+                Control::Continue
+            }
+            None => Control::Continue,
+        }
+    }
+
+    #[test]
+    fn run_until_exit() {
+        let plain = KeyModifiers::NONE;
+        let mut terminal = terminal(&[
+            key(KeyCode::Char('a'), plain),
+            key(KeyCode::Char('b'), plain),
+            key(KeyCode::Char('q'), plain),
+        ]);
+        let mut count = 0;
+
+        let outcome = terminal
+            .run_stateful(|| Counter, &mut count, 60, count_until_q)
+            .unwrap();
+
+        assert_eq!(outcome, Outcome::Finished);
+        assert_eq!(terminal.backend().screen_lines(), ["2", "", ""]);
+    }
+
+    #[test]
+    fn run_until_interrupted() {
+        let mut terminal = terminal(&[
+            key(KeyCode::Char('a'), KeyModifiers::NONE),
+            key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+        ]);
+        let mut count = 0;
+
+        let outcome = terminal
+            .run_stateful(|| Counter, &mut count, 60, count_until_q)
+            .unwrap();
+
+        assert_eq!(outcome, Outcome::Interrupted);
+        assert_eq!(count, 1);
+    }
+}
@@ -1,17 +1,74 @@
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
 use crate::backend::Backend;
 use crate::backend::ClearType;
 use crate::buffer::Buffer;
+use crate::buffer::Cell;
+use crate::component::Component;
+use crate::component::copy::Copyable;
+use crate::component::image;
 use crate::layout::Position;
 use crate::layout::Rect;
 use crate::layout::Size;
-use crate::terminal::frame::CompletedFrame;
+use crate::style;
+use crate::style::Attributes;
+use crate::style::Color;
+use crate::style::ColorMode;
+use crate::style::Style;
+use crate::style::StyledString;
+use crate::terminal::Capabilities;
+use crate::terminal::Cleanup;
+use crate::terminal::DefaultColors;
 use crate::terminal::Frame;
+use crate::terminal::RegionId;
+use crate::terminal::Regions;
 use crate::terminal::Viewport;
+use crate::terminal::clipboard;
+use crate::terminal::frame::CompletedFrame;
+use crate::terminal::frame::RenderStats;
+use crate::theme::Theme;
 
+/// The options a [Terminal] is created with, set through a
+/// [TerminalBuilder](crate::terminal::TerminalBuilder).
+#[derive(Debug, Clone)]
 pub struct Options {
     /// Whether the terminal should respect CTRL+C
-    respect_exit: bool,
-    viewport: Viewport,
+    pub(crate) respect_exit: bool,
+    pub(crate) viewport: Viewport,
+    /// The features supported by the terminal, usually probed by [try_init](crate::try_init)
+    pub(crate) capabilities: Capabilities,
+    /// The default colors of the terminal, usually queried by [try_init](crate::try_init)
+    pub(crate) colors: DefaultColors,
+    /// Whether raw mode is only entered while interactive components are active
+    pub(crate) lazy_raw_mode: bool,
+    /// The color mode to draw with, or [None] to detect it from the capabilities
+    pub(crate) color_mode: Option<ColorMode>,
+    pub(crate) frame_rate: u32,
+    pub(crate) theme: Theme,
+    pub(crate) cleanup: Cleanup,
+    /// Whether CTRL+Z suspends the process
+    pub(crate) suspend: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            respect_exit: true,
+            viewport: Viewport::Inline(1),
+            capabilities: Capabilities::default(),
+            colors: DefaultColors::default(),
+            lazy_raw_mode: false,
+            color_mode: None,
+            frame_rate: 30,
+            theme: Theme::default(),
+            cleanup: Cleanup::default(),
+            suspend: false,
+        }
+    }
 }
 
 /// An abstraction over output through a given backend
@@ -26,34 +83,75 @@ pub struct Terminal<B: Backend> {
     last_known_area: Rect,
     last_known_cursor_pos: Position,
     respect_exit: bool,
+    capabilities: Capabilities,
+    colors: DefaultColors,
+    /// Whether the background is light, from the default colors or the environment
+    light: bool,
+    /// Reports the queried background through [style::is_light] while the terminal is alive
+    _background: Option<style::Background>,
+    /// The column where the last print left off, or 0 if it ended with a newline
+    print_column: u16,
+    regions: Regions,
+    /// The row where output printed above a footer continues
+    output_row: u16,
+    lazy_raw_mode: bool,
+    /// Whether raw mode was entered by [enter_interactive](Self::enter_interactive)
+    interactive: bool,
+    color_mode: ColorMode,
+    frame_rate: u32,
+    theme: Theme,
+    cleanup: Cleanup,
+    suspend: bool,
+    /// Statistics about the last draw
+    stats: RenderStats,
+    /// When the last draw started
+    last_draw: Option<Instant>,
 }
 
 impl<B: Backend> Terminal<B> {
     /// Create a new terminal with the given handle (implementing [Write]).
     /// Returns none if terminal width cannot be retrieved
     pub fn new(backend: B) -> Result<Self, B::Error> {
-        Self::with_options(
-            backend,
-            Options {
-                respect_exit: true,
-                viewport: Viewport::Inline(1),
-            },
-        )
+        Self::with_options(backend, Options::default())
     }
 
     pub fn with_options(mut backend: B, options: Options) -> Result<Self, B::Error> {
         let area = match options.viewport {
-            Viewport::Inline(height) => backend.size()?.into(),
+            Viewport::Inline(_) | Viewport::Fit | Viewport::Footer(_) => backend.size()?.into(),
             Viewport::Fixed(rect) => rect,
         };
 
+        let mut output_row = 0;
         let (viewport_area, cursor_pos) = match options.viewport {
             Viewport::Inline(height) => {
                 compute_inline_size(&mut backend, height, area.as_size(), 0)?
             }
+            Viewport::Fit => compute_inline_size(&mut backend, 1, area.as_size(), 0)?,
+            Viewport::Footer(height) => {
+                // Output continues on the line after the cursor if it isn't at the start of one
+                let cursor = backend.get_cursor_position()?;
+                let footer;
+                (footer, output_row) = place_footer(
+                    &mut backend,
+                    height,
+                    area.as_size(),
+                    cursor.y + u16::from(cursor.x > 0),
+                )?;
+                (footer, cursor)
+            }
             Viewport::Fixed(area) => (area, area.as_position()),
         };
 
+        // Normal output continues from the top of the viewport until it is first used
+        if options.lazy_raw_mode {
+            backend.set_cursor_position(viewport_area.as_position())?;
+        }
+
+        let color_mode = options
+            .color_mode
+            .unwrap_or_else(|| options.capabilities.color_mode());
+        let detected_light = options.colors.is_light();
+
         Ok(Self {
             backend,
             buffers: [Buffer::empty(viewport_area), Buffer::empty(viewport_area)],
@@ -64,14 +162,37 @@ impl<B: Backend> Terminal<B> {
             last_known_area: area,
             last_known_cursor_pos: cursor_pos,
             respect_exit: options.respect_exit,
+            capabilities: options.capabilities,
+            light: detected_light.unwrap_or_else(style::env_is_light),
+            _background: detected_light.map(style::Background::detected),
+            colors: options.colors,
+            print_column: 0,
+            regions: Regions::default(),
+            output_row,
+            lazy_raw_mode: options.lazy_raw_mode,
+            interactive: false,
+            color_mode,
+            frame_rate: options.frame_rate,
+            theme: options.theme,
+            cleanup: options.cleanup,
+            suspend: options.suspend,
+            stats: RenderStats::default(),
+            last_draw: None,
         })
     }
 
     pub const fn get_frame(&mut self) -> Frame<'_> {
+        let buffer = &mut self.buffers[self.current];
         Frame {
             cursor_position: None,
-            viewport_area: self.viewport_area,
-            buffer: self.current_buffer_mut(),
+            // Usually the viewport area, but taller while rendering a fitting viewport
+            viewport_area: buffer.area,
+            buffer,
+            capabilities: &self.capabilities,
+            regions: &self.regions,
+            theme: &self.theme,
+            light: self.light,
+            content_height: None,
         }
     }
 
@@ -79,6 +200,53 @@ impl<B: Backend> Terminal<B> {
         &mut self.buffers[self.current]
     }
 
+    /// The features supported by the terminal
+    pub const fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// The default foreground color of the terminal, if known
+    pub const fn foreground_color(&self) -> Option<Color> {
+        self.colors.foreground
+    }
+
+    /// The default background color of the terminal, if known
+    pub const fn background_color(&self) -> Option<Color> {
+        self.colors.background
+    }
+
+    /// Whether the terminal background is light, which decides the color
+    /// [AdaptiveColor](style::AdaptiveColor)s [resolve](style::AdaptiveColor::resolve) to.
+    ///
+    /// This is worked out from the [background color](Self::background_color) if the terminal
+    /// reported one, and otherwise from the `COLORFGBG` environment variable.
+    /// [AdaptiveColor](style::AdaptiveColor)s converted while the terminal draws, or while it is the most recently created terminal
+    /// with a known background, use this through [style::is_light].
+    pub const fn is_light(&self) -> bool {
+        self.light
+    }
+
+    /// The [ColorMode] colors are converted to before they are drawn
+    pub const fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    /// The frame rate live components should be drawn at, for example by
+    /// [run_stateful](Self::run_stateful)
+    pub const fn frame_rate(&self) -> u32 {
+        self.frame_rate
+    }
+
+    /// The [Theme] components are styled with
+    pub const fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Statistics about the last [draw](Self::draw)
+    pub const fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
     pub const fn backend(&self) -> &B {
         &self.backend
     }
@@ -90,30 +258,46 @@ impl<B: Backend> Terminal<B> {
     /// Gets a diff between the current and previous buffers and passes it to the backend to be
     /// drawn
     pub fn flush(&mut self) -> Result<(), B::Error> {
+        self.flush_changes().map(|_| ())
+    }
+
+    /// Draw the changes since the previous buffer, returning the number of cells drawn.
+    fn flush_changes(&mut self) -> Result<usize, B::Error> {
         let previous_buffer = &self.buffers[1 - self.current];
         let current_buffer = &self.buffers[self.current];
+
+        // Images drawn with the kitty graphics protocol stay on screen until they are deleted
+        let deletions = image::kitty_deletions(previous_buffer, current_buffer);
+        if !deletions.is_empty() {
+            self.backend.write_raw(&deletions)?;
+        }
+
         let updates = previous_buffer.diff(current_buffer);
         if let Some((col, row, _)) = updates.last() {
             self.last_known_cursor_pos = Position { x: *col, y: *row };
         }
 
-        self.backend.draw(updates.into_iter())
+        let changed_cells = updates.len();
+        self.backend.draw(updates.into_iter())?;
+        Ok(changed_cells)
     }
 
+    /// Resize the viewport to fit a new terminal size, clearing it so the next
+    /// [draw](Self::draw) redraws it fully.
+    ///
+    /// Terminal emulators rewrap lines when the width changes, so an inline viewport is moved to
+    /// wherever the cursor ended up and everything from there down is cleared, including any
+    /// rewrapped fragments of earlier frames.
     pub fn resize(&mut self, area: Rect) -> Result<(), B::Error> {
         let next_area = match self.viewport {
-            Viewport::Inline(height) => {
-                let offset_in_previous_viewport = self
-                    .last_known_cursor_pos
-                    .y
-                    .saturating_sub(self.viewport_area.top());
-                compute_inline_size(
-                    &mut self.backend,
-                    height,
-                    area.as_size(),
-                    offset_in_previous_viewport,
-                )?
-                .0
+            Viewport::Inline(height) => self.reanchor_inline(self.region_height(height), area)?,
+            Viewport::Fit => self.reanchor_inline(self.viewport_area.height, area)?,
+            Viewport::Footer(height) => {
+                let height = self.region_height(height);
+                let (footer, output_row) =
+                    place_footer(&mut self.backend, height, area.as_size(), self.output_row)?;
+                self.output_row = output_row;
+                footer
             }
             Viewport::Fixed(_) => area,
         };
@@ -122,9 +306,25 @@ impl<B: Backend> Terminal<B> {
         self.clear()?;
 
         self.last_known_area = area;
+        self.print_column = 0;
         Ok(())
     }
 
+    /// Compute where an inline viewport is after a resize from the position of the cursor.
+    fn reanchor_inline(&mut self, height: u16, area: Rect) -> Result<Rect, B::Error> {
+        let offset_in_previous_viewport = self
+            .last_known_cursor_pos
+            .y
+            .saturating_sub(self.viewport_area.top());
+        let (next_area, _) = compute_inline_size(
+            &mut self.backend,
+            height,
+            area.as_size(),
+            offset_in_previous_viewport,
+        )?;
+        Ok(next_area)
+    }
+
     pub fn draw<F>(&mut self, render_callback: F) -> Result<CompletedFrame<'_>, B::Error>
     where
         F: FnOnce(&mut Frame),
@@ -140,18 +340,70 @@ impl<B: Backend> Terminal<B> {
         F: FnOnce(&mut Frame) -> Result<(), E>,
         E: Into<B::Error>,
     {
+        self.outside_interactive(|terminal| terminal.draw_frame(render_callback))?;
+
+        Ok(CompletedFrame {
+            buffer: &self.buffers[1 - self.current],
+            area: self.last_known_area,
+            stats: self.stats,
+        })
+    }
+
+    /// Render and draw a frame, see [try_draw](Self::try_draw).
+    fn draw_frame<F, E>(&mut self, render_callback: F) -> Result<(), B::Error>
+    where
+        F: FnOnce(&mut Frame) -> Result<(), E>,
+        E: Into<B::Error>,
+    {
+        let start = Instant::now();
+        let frame_time = self.last_draw.map(|last| start.duration_since(last));
+        self.last_draw = Some(start);
+        let bytes_before = self.backend.bytes_written();
+
         self.autoresize()?;
+        let render_start = Instant::now();
+
+        if self.viewport == Viewport::Fit {
+            // Render into the rest of the screen so content isn't clipped before it's measured
+            let area = Rect {
+                height: self.last_known_area.height,
+                ..self.viewport_area
+            };
+            self.buffers[self.current].resize(area);
+        }
 
         let mut frame = self.get_frame();
 
-        render_callback(&mut frame).map_err(Into::into)?;
+        let light = frame.is_light();
+        style::with_light(light, || render_callback(&mut frame)).map_err(Into::into)?;
+
+        let mut cursor_position = frame.cursor_position;
+        let content_height = frame.content_height;
+        downgrade_colors(&mut self.buffers[self.current], self.color_mode);
+
+        if self.viewport == Viewport::Fit {
+            let height = content_height.unwrap_or_else(|| used_height(&self.buffers[self.current]));
+            let top = self.viewport_area.top();
+            self.resize_viewport(height.max(1))?;
 
-        let cursor_position = frame.cursor_position;
+            // The viewport moves up if the terminal had to scroll to make room for it
+            let scrolled = top - self.viewport_area.top();
+            if let Some(position) = &mut cursor_position {
+                position.y = position.y.saturating_sub(scrolled);
+            }
+        }
 
-        self.flush()?;
+        let flush_start = Instant::now();
+        let render_time = flush_start - render_start;
+        let changed_cells = self.flush_changes()?;
 
         match cursor_position {
-            None => self.hide_cursor()?,
+            None => {
+                self.hide_cursor()?;
+                // Terminals move the cursor along with the text when they rewrap lines on resize,
+                // so parking it at the start of the viewport keeps track of where the viewport is
+                self.set_cursor_position(self.viewport_area.as_position())?;
+            }
             Some(position) => {
                 self.show_cursor()?;
                 self.set_cursor_position(position)?
@@ -162,12 +414,586 @@ impl<B: Backend> Terminal<B> {
 
         self.backend.flush()?;
 
-        let completed_frame = CompletedFrame {
-            buffer: &self.buffers[1 - self.current],
-            area: self.last_known_area,
+        self.stats = RenderStats {
+            changed_cells,
+            bytes_written: self.backend.bytes_written() - bytes_before,
+            render_time,
+            flush_time: flush_start.elapsed(),
+            frame_time,
+        };
+        Ok(())
+    }
+
+    /// Leave the last drawn frame of an inline viewport behind as scrollback and start a fresh
+    /// viewport below it.
+    ///
+    /// Blank rows at the bottom of the frame are dropped, so a step which only used part of its
+    /// viewport doesn't leave a gap. The new viewport has the same height and is empty until the
+    /// next [draw](Self::draw). This does nothing for viewports which aren't inline.
+    pub fn commit(&mut self) -> Result<(), B::Error> {
+        if !self.viewport.is_inline() {
+            return Ok(());
+        }
+
+        self.move_below_frame()?;
+        self.anchor_viewport()
+    }
+
+    /// Enter raw mode for an interactive or live component if the terminal was initialised with
+    /// [init_lazy](crate::init_lazy), and otherwise do nothing.
+    ///
+    /// Output may have been written normally since the last interactive component, so an inline
+    /// viewport is moved to the cursor. This is done automatically by
+    /// [run_stateful](Self::run_stateful) and [spawn](Self::spawn). Drawing, printing or inserting
+    /// lines outside of an interactive component enters raw mode for just that call, so they too
+    /// start from the cursor and leave it below the viewport.
+    pub fn enter_interactive(&mut self) -> Result<(), B::Error> {
+        if !self.lazy_raw_mode || self.interactive {
+            return Ok(());
+        }
+
+        self.backend.enable_raw_mode()?;
+        self.interactive = true;
+        if self.viewport.is_inline() {
+            self.anchor_viewport()?;
+        }
+        Ok(())
+    }
+
+    /// Leave raw mode after an interactive or live component if it was entered with
+    /// [enter_interactive](Self::enter_interactive).
+    ///
+    /// The last drawn frame stays on screen and the cursor is left on the line below it, so
+    /// normal output such as [println!] continues from there.
+    pub fn leave_interactive(&mut self) -> Result<(), B::Error> {
+        if !self.interactive {
+            return Ok(());
+        }
+
+        if self.viewport.is_inline() {
+            self.move_below_frame()?;
+        }
+        self.show_cursor()?;
+        self.backend.flush()?;
+        self.backend.disable_raw_mode()?;
+        self.interactive = false;
+        Ok(())
+    }
+
+    /// Apply the [Cleanup] policy to the viewport and leave the cursor visible on a fresh line
+    /// below it, with colors and attributes reset, so that whatever runs next starts cleanly.
+    ///
+    /// Between interactive components of a lazy terminal, normal output may have been written
+    /// below the viewport, so the cursor is left where it is.
+    fn restore(&mut self) -> Result<(), B::Error> {
+        let area = self.viewport_area;
+        // The viewport isn't touched outside of raw mode, including when raw mode was already left
+        // with `tdrop::restore` before the terminal was dropped
+        let in_raw_mode = self.in_raw_mode() && self.backend.is_raw_mode_enabled()?;
+        match std::mem::take(&mut self.cleanup) {
+            _ if !in_raw_mode => {}
+            Cleanup::Keep => match self.viewport {
+                Viewport::Inline(_) | Viewport::Fit => self.move_below_frame()?,
+                Viewport::Fixed(_) | Viewport::Footer(_) => {
+                    self.backend.reset_scroll_region()?;
+                    self.backend.set_cursor_position(Position {
+                        x: 0,
+                        y: area.bottom().saturating_sub(1),
+                    })?;
+                    self.backend.append_lines(1)?;
+                }
+            },
+            cleanup => {
+                self.clear()?;
+                self.backend.reset_scroll_region()?;
+
+                // Output continues where the viewport started, or below the output above a footer
+                let y = match self.viewport {
+                    Viewport::Footer(_) => self.output_row,
+                    _ => area.top(),
+                };
+                self.backend.set_cursor_position(Position { x: 0, y })?;
+
+                if let Cleanup::Summary(summary) = cleanup {
+                    // The summary is kept to a single row, cut off at the width of the viewport
+                    let mut row = Buffer::empty(Rect {
+                        x: 0,
+                        y,
+                        width: area.width,
+                        height: 1,
+                    });
+                    row.set_string(0, y, summary, Style::default());
+                    let cells = row
+                        .content
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, cell)| !cell.skip);
+                    self.backend
+                        .draw(cells.map(|(x, cell)| (x as u16, y, cell)))?;
+                    self.backend.set_cursor_position(Position { x: 0, y })?;
+                    self.backend.append_lines(1)?;
+                }
+            }
+        }
+
+        self.reset_cursor_and_style()
+    }
+
+    /// Show the cursor and reset colors and attributes.
+    fn reset_cursor_and_style(&mut self) -> Result<(), B::Error> {
+        self.backend.show_cursor()?;
+        self.backend.write_raw("\x1b[0m")?;
+        self.backend.flush()
+    }
+
+    /// Whether the terminal is currently in raw mode.
+    const fn in_raw_mode(&self) -> bool {
+        !self.lazy_raw_mode || self.interactive
+    }
+
+    /// Run `f`, entering raw mode around it if the terminal is lazy and no interactive component
+    /// is active.
+    ///
+    /// Normal output such as [println!] may have moved the cursor since the viewport was last
+    /// used, so an inline viewport is anchored at the cursor first rather than drawing over that
+    /// output, and the cursor is left below the viewport afterwards.
+    fn outside_interactive<T, F>(&mut self, f: F) -> Result<T, B::Error>
+    where
+        F: FnOnce(&mut Self) -> Result<T, B::Error>,
+    {
+        if self.in_raw_mode() {
+            return f(self);
+        }
+
+        self.enter_interactive()?;
+        let result = f(self);
+        let left = self.leave_interactive();
+        let value = result?;
+        left?;
+        Ok(value)
+    }
+
+    /// Hand the terminal over to something else while `f` runs, such as an editor or pager
+    /// launched as a child process, returning what `f` returns.
+    ///
+    /// Raw mode is left and the cursor is shown on the line below the last frame of an inline
+    /// viewport, which stays behind like after [commit](Self::commit). Afterwards raw mode is
+    /// entered again and the viewport is anchored wherever the child process left the cursor, to
+    /// be fully redrawn by the next [draw](Self::draw).
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    ///
+    /// let mut terminal = tdrop::init();
+    /// let status = terminal.suspend_while(|| Command::new("vi").status())??;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn suspend_while<F, R>(&mut self, f: F) -> Result<R, B::Error>
+    where
+        F: FnOnce() -> R,
+    {
+        self.release()?;
+        let result = f();
+        self.reclaim()?;
+        Ok(result)
+    }
+
+    /// Give the terminal back to the shell and stop the process, as CTRL+Z normally does, then
+    /// take it over again once the process is continued.
+    fn suspend(&mut self) -> Result<(), B::Error> {
+        self.release()?;
+        self.backend.suspend_process()?;
+        self.reclaim()
+    }
+
+    /// Leave raw mode with the cursor visible below the viewport, so that the terminal behaves
+    /// normally for whatever uses it next.
+    fn release(&mut self) -> Result<(), B::Error> {
+        // Between interactive components of a lazy terminal it already behaves normally
+        if !self.in_raw_mode() {
+            return Ok(());
+        }
+
+        match self.viewport {
+            Viewport::Inline(_) | Viewport::Fit => self.move_below_frame()?,
+            Viewport::Footer(_) => {
+                self.clear()?;
+                self.backend.reset_scroll_region()?;
+                self.backend.set_cursor_position(Position {
+                    x: 0,
+                    y: self.output_row,
+                })?;
+            }
+            Viewport::Fixed(_) => {}
+        }
+
+        self.reset_cursor_and_style()?;
+        self.backend.disable_raw_mode()
+    }
+
+    /// Enter raw mode again after [release](Self::release) and anchor the viewport wherever the
+    /// cursor was left. The viewport is cleared, so the next draw redraws it fully.
+    fn reclaim(&mut self) -> Result<(), B::Error> {
+        if !self.in_raw_mode() {
+            return Ok(());
+        }
+
+        self.backend.enable_raw_mode()?;
+        if !matches!(self.viewport, Viewport::Fixed(_)) {
+            self.last_known_area = self.size()?.into();
+        }
+
+        match self.viewport {
+            Viewport::Inline(_) | Viewport::Fit => self.anchor_viewport(),
+            Viewport::Footer(height) => {
+                let height = self.region_height(height);
+                let cursor = self.backend.get_cursor_position()?;
+                let (footer, output_row) = place_footer(
+                    &mut self.backend,
+                    height,
+                    self.last_known_area.as_size(),
+                    cursor.y + u16::from(cursor.x > 0),
+                )?;
+                self.output_row = output_row;
+                self.print_column = 0;
+                self.set_viewport_area(footer);
+                self.clear()
+            }
+            Viewport::Fixed(_) => self.clear(),
+        }
+    }
+
+    /// Move the cursor to the start of the line below what was last drawn in an inline viewport,
+    /// scrolling if it is at the bottom of the screen.
+    fn move_below_frame(&mut self) -> Result<(), B::Error> {
+        // The previous buffer holds what is on screen after the last draw
+        let kept = used_height(&self.buffers[1 - self.current]);
+
+        let top = self.viewport_area.top();
+        if kept > 0 {
+            // Moving down from the last kept row scrolls the screen if it is at the bottom
+            self.backend.set_cursor_position(Position {
+                x: 0,
+                y: top + kept - 1,
+            })?;
+            self.backend.append_lines(1)?;
+        } else {
+            self.backend
+                .set_cursor_position(Position { x: 0, y: top })?;
+        }
+        Ok(())
+    }
+
+    /// Start a fresh, empty inline viewport at the cursor.
+    fn anchor_viewport(&mut self) -> Result<(), B::Error> {
+        let height = match self.viewport {
+            Viewport::Inline(height) => self.region_height(height),
+            Viewport::Fit | Viewport::Footer(_) | Viewport::Fixed(_) => 1,
         };
 
-        Ok(completed_frame)
+        let (area, cursor_pos) =
+            compute_inline_size(&mut self.backend, height, self.last_known_area.as_size(), 0)?;
+        self.set_viewport_area(area);
+        self.last_known_cursor_pos = cursor_pos;
+        self.print_column = 0;
+        self.clear()
+    }
+
+    /// Insert lines above an inline viewport, leaving them behind in the terminal's scrollback.
+    ///
+    /// `draw_fn` renders the lines into a [Buffer] which is as wide as the viewport and `height`
+    /// rows tall. The viewport stays pinned below the inserted lines, scrolling the terminal as
+    /// needed, and is fully redrawn by the next [draw](Self::draw). Lines inserted above a
+    /// [Footer](Viewport::Footer) are added to the output scrolling above it instead, leaving the
+    /// footer as it is. This does nothing for [Fixed](Viewport::Fixed) viewports.
+    pub fn insert_before<F>(&mut self, height: u16, draw_fn: F) -> Result<(), B::Error>
+    where
+        F: FnOnce(&mut Buffer),
+    {
+        if !self.viewport.has_output_above() {
+            return Ok(());
+        }
+        self.outside_interactive(|terminal| terminal.insert_buffer(height, draw_fn))
+    }
+
+    /// Insert lines above the viewport, see [insert_before](Self::insert_before).
+    fn insert_buffer<F>(&mut self, height: u16, draw_fn: F) -> Result<(), B::Error>
+    where
+        F: FnOnce(&mut Buffer),
+    {
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width: self.viewport_area.width,
+            height,
+        };
+        let mut buffer = Buffer::empty(area);
+        style::with_light(self.light, || draw_fn(&mut buffer));
+        downgrade_colors(&mut buffer, self.color_mode);
+        if let Viewport::Footer(_) = self.viewport {
+            return self.insert_above_footer(&buffer);
+        }
+        let mut remaining = buffer.content.as_slice();
+
+        // i32 is used to avoid having to worry about overflow and underflow
+        let mut drawn_height = i32::from(self.viewport_area.top());
+        let mut buffer_height = i32::from(height);
+        let viewport_height = i32::from(self.viewport_area.height);
+        let screen_height = i32::from(self.last_known_area.height);
+
+        // Draw the lines a screen at a time until what remains fits on screen alongside the
+        // viewport. Each chunk scrolls the screen only as much as is needed to make room for it.
+        while buffer_height + viewport_height > screen_height {
+            let to_draw = buffer_height.min(screen_height);
+            let scroll_up = 0.max(drawn_height + to_draw - screen_height);
+            self.scroll_up(scroll_up as u16)?;
+            remaining =
+                self.draw_lines((drawn_height - scroll_up) as u16, to_draw as u16, remaining)?;
+            drawn_height += to_draw - scroll_up;
+            buffer_height -= to_draw;
+        }
+
+        // The rest of the lines and the viewport now fit on screen, although existing output may
+        // still have to scroll up to make room. If the viewport wasn't at the bottom of the
+        // screen there may be enough room already, in which case nothing scrolls.
+        let scroll_up = 0.max(drawn_height + buffer_height + viewport_height - screen_height);
+        self.scroll_up(scroll_up as u16)?;
+        self.draw_lines(
+            (drawn_height - scroll_up) as u16,
+            buffer_height as u16,
+            remaining,
+        )?;
+        drawn_height += buffer_height - scroll_up;
+
+        // Anything inserted ends the line a print may have left unfinished
+        self.print_column = 0;
+        self.set_viewport_area(Rect {
+            y: drawn_height as u16,
+            ..self.viewport_area
+        });
+
+        // The old viewport content was overwritten by the inserted lines or scrolled out of the
+        // way, but whatever is left in the new viewport area must go before the next draw.
+        self.clear()
+    }
+
+    /// Draw lines below the output above a footer, scrolling the rows above the footer as needed.
+    fn insert_above_footer(&mut self, buffer: &Buffer) -> Result<(), B::Error> {
+        let region = self.viewport_area.top();
+        let mut remaining = buffer.content.as_slice();
+        let mut lines = buffer.area.height;
+
+        while lines > 0 && region > 0 {
+            if self.output_row >= region {
+                // Line feeds on the last row of the scroll region leave the footer alone
+                let scroll = lines.min(region);
+                self.backend.set_cursor_position(Position {
+                    x: 0,
+                    y: region - 1,
+                })?;
+                self.backend.append_lines(scroll)?;
+                self.output_row = region - scroll;
+            }
+
+            let to_draw = lines.min(region - self.output_row);
+            remaining = self.draw_lines(self.output_row, to_draw, remaining)?;
+            self.output_row += to_draw;
+            lines -= to_draw;
+        }
+
+        self.print_column = 0;
+        Ok(())
+    }
+
+    /// Render a component once above an inline viewport, leaving it behind in the terminal's
+    /// scrollback. See [insert_before](Self::insert_before).
+    ///
+    /// The component is as wide as the viewport and as tall as reported by
+    /// [Component::height]. Otherwise its height is measured from the rows it draws to, up to
+    /// the height of the screen.
+    pub fn render_once<C: Component>(&mut self, component: C) -> Result<(), B::Error> {
+        let width = self.viewport_area.width;
+        let reported = component.height(width);
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width,
+            height: reported.unwrap_or(self.last_known_area.height),
+        };
+
+        let mut buffer = Buffer::empty(area);
+        style::with_light(self.light, || component.render(area, &mut buffer));
+        let height = reported.unwrap_or_else(|| used_height(&buffer));
+        buffer.resize(Rect { height, ..area });
+
+        self.insert_before(height, |rows| rows.content = buffer.content)
+    }
+
+    /// Insert lines of styled text above an inline viewport, leaving them behind in the
+    /// terminal's scrollback. See [insert_before](Self::insert_before).
+    ///
+    /// Each [StyledString] starts a new line. Newlines within them also start new lines and lines
+    /// wider than the viewport are wrapped.
+    pub fn insert_lines<I, D>(&mut self, lines: I) -> Result<(), B::Error>
+    where
+        I: IntoIterator<Item = StyledString<D>>,
+        D: Display,
+    {
+        let mut wrapper = Wrapper::new(self.viewport_area.width);
+        for line in lines {
+            wrapper.push(&line);
+            wrapper.newline();
+        }
+        let (rows, _) = wrapper.finish();
+        self.insert_rows(rows)
+    }
+
+    /// Print styled text above an inline viewport, like [print!] does for standard output.
+    ///
+    /// Text continues on the line where the last print left off, so a line can be built up over
+    /// several calls. Newlines always return to the start of the line, even in raw mode, and
+    /// lines wider than the viewport are wrapped. The viewport moves down below the printed text,
+    /// so later draws land in the right place. This does nothing for viewports which aren't
+    /// inline.
+    pub fn print<D: Display>(&mut self, text: StyledString<D>) -> Result<(), B::Error> {
+        self.print_styled([text])
+    }
+
+    /// Print styled text followed by a newline above an inline viewport, like [println!] does
+    /// for standard output. See [print](Self::print).
+    pub fn println<D: Display>(&mut self, text: StyledString<D>) -> Result<(), B::Error> {
+        self.write_styled([text], true)
+    }
+
+    /// Print several pieces of styled text one after another above an inline viewport. See
+    /// [print](Self::print).
+    pub fn print_styled<I, D>(&mut self, strings: I) -> Result<(), B::Error>
+    where
+        I: IntoIterator<Item = StyledString<D>>,
+        D: Display,
+    {
+        self.write_styled(strings, false)
+    }
+
+    /// Like [print_styled](Self::print_styled), but followed by a newline.
+    pub fn println_styled<I, D>(&mut self, strings: I) -> Result<(), B::Error>
+    where
+        I: IntoIterator<Item = StyledString<D>>,
+        D: Display,
+    {
+        self.write_styled(strings, true)
+    }
+
+    fn write_styled<I, D>(&mut self, strings: I, newline: bool) -> Result<(), B::Error>
+    where
+        I: IntoIterator<Item = StyledString<D>>,
+        D: Display,
+    {
+        if !self.viewport.has_output_above() {
+            return Ok(());
+        }
+        self.outside_interactive(|terminal| terminal.print_rows(strings, newline))
+    }
+
+    /// Print styled text above the viewport, see [print](Self::print).
+    fn print_rows<I, D>(&mut self, strings: I, newline: bool) -> Result<(), B::Error>
+    where
+        I: IntoIterator<Item = StyledString<D>>,
+        D: Display,
+    {
+        // An unfinished line is directly above where output continues, unless it has since
+        // scrolled away
+        let output_row = match self.viewport {
+            Viewport::Footer(_) => self.output_row,
+            _ => self.viewport_area.top(),
+        };
+        let continues = self.print_column > 0 && output_row > 0;
+
+        let mut wrapper = Wrapper::new(self.viewport_area.width);
+        if continues {
+            wrapper.skip(self.print_column);
+        }
+        for string in strings {
+            wrapper.push(&string);
+        }
+        if newline {
+            wrapper.newline();
+        }
+        let (mut rows, last_row) = wrapper.finish();
+
+        let column = u16::try_from(last_row.len()).unwrap_or(u16::MAX);
+        if !last_row.is_empty() {
+            rows.push(last_row);
+        }
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        // Reclaim the unfinished line so it is drawn over. Its cells before the print column are
+        // skipped, leaving what was already printed in place.
+        if continues {
+            match self.viewport {
+                Viewport::Footer(_) => self.output_row -= 1,
+                _ => self.set_viewport_area(Rect {
+                    y: self.viewport_area.top() - 1,
+                    ..self.viewport_area
+                }),
+            }
+        }
+
+        self.insert_rows(rows)?;
+        self.print_column = column;
+        Ok(())
+    }
+
+    /// Insert rows of cells above an inline viewport. See [insert_before](Self::insert_before).
+    fn insert_rows(&mut self, rows: Vec<Vec<Cell>>) -> Result<(), B::Error> {
+        let height = u16::try_from(rows.len()).unwrap_or(u16::MAX);
+
+        self.insert_before(height, |buffer| {
+            let width = usize::from(buffer.area.width);
+            for (y, row) in rows.into_iter().take(usize::from(height)).enumerate() {
+                for (x, cell) in row.into_iter().take(width).enumerate() {
+                    buffer.content[y * width + x] = cell;
+                }
+            }
+        })
+    }
+
+    /// Scroll the whole screen up by the given number of lines.
+    fn scroll_up(&mut self, lines: u16) -> Result<(), B::Error> {
+        if lines > 0 {
+            self.set_cursor_position(Position {
+                x: 0,
+                y: self.last_known_area.height.saturating_sub(1),
+            })?;
+            self.backend.append_lines(lines)?;
+        }
+        Ok(())
+    }
+
+    /// Draw full-width rows of cells starting at the given row, returning the cells which
+    /// weren't drawn.
+    fn draw_lines<'a>(
+        &mut self,
+        y: u16,
+        lines: u16,
+        cells: &'a [Cell],
+    ) -> Result<&'a [Cell], B::Error> {
+        let width = usize::from(self.viewport_area.width);
+        let (to_draw, remaining) = cells.split_at((width * usize::from(lines)).min(cells.len()));
+
+        if lines > 0 {
+            let iter = to_draw
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| !cell.skip)
+                .map(|(i, cell)| ((i % width) as u16, y + (i / width) as u16, cell));
+            self.backend.draw(iter)?;
+            self.backend.flush()?;
+        }
+
+        Ok(remaining)
     }
 
     pub fn hide_cursor(&mut self) -> Result<(), B::Error> {
@@ -189,18 +1015,161 @@ impl<B: Backend> Terminal<B> {
         Ok(())
     }
 
+    /// Copy text to the system clipboard using OSC 52.
+    ///
+    /// The terminal emulator sets the clipboard itself, so this also works over SSH. Support
+    /// varies between terminals and some only allow it after user configuration, in which case
+    /// the request is silently ignored.
+    pub fn copy_to_clipboard(&mut self, text: &str) -> Result<(), B::Error> {
+        self.backend.write_raw(&clipboard::copy_sequence(text))?;
+        self.backend.flush()
+    }
+
+    /// Copy the selected entry of a [Copyable] state to the clipboard, returning whether anything
+    /// was selected.
+    pub fn copy_selected<C: Copyable>(&mut self, state: &C) -> Result<bool, B::Error> {
+        match state.selected_text() {
+            Some(text) => {
+                self.copy_to_clipboard(&text)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
     pub fn get_cursor_position(&mut self) -> Result<Position, B::Error> {
         self.backend.get_cursor_position()
     }
 
+    /// The height of a viewport configured to be `height` rows tall, which grows while its
+    /// regions need more rows than that.
+    fn region_height(&self, height: u16) -> u16 {
+        height.max(self.regions.height())
+    }
+
     fn set_viewport_area(&mut self, area: Rect) {
         self.buffers[self.current].resize(area);
         self.buffers[1 - self.current].resize(area);
         self.viewport_area = area;
     }
 
+    /// Change the height of an inline viewport.
+    ///
+    /// The top of the viewport stays where it is unless the terminal has to scroll to make room
+    /// for a taller viewport, and rows given up by a shorter viewport are cleared. A
+    /// [Fit](Viewport::Fit) viewport is measured again on the next [draw](Self::draw), and this
+    /// does nothing for viewports which aren't inline. The viewport stays tall enough for its
+    /// [regions](Self::add_region).
+    pub fn set_viewport_height(&mut self, height: u16) -> Result<(), B::Error> {
+        match self.viewport {
+            Viewport::Inline(_) => self.viewport = Viewport::Inline(height),
+            Viewport::Footer(_) => self.viewport = Viewport::Footer(height),
+            Viewport::Fit | Viewport::Fixed(_) => return Ok(()),
+        }
+        self.fit_regions()
+    }
+
+    /// Resize the viewport after its regions changed, growing it if they need more rows than its
+    /// configured height and shrinking it back once they don't.
+    fn fit_regions(&mut self) -> Result<(), B::Error> {
+        match self.viewport {
+            Viewport::Inline(height) => self.resize_viewport(self.region_height(height))?,
+            Viewport::Footer(_) => self.resize(self.last_known_area)?,
+            Viewport::Fit | Viewport::Fixed(_) => return Ok(()),
+        }
+        self.backend.flush()
+    }
+
+    /// Add a region of the given height below the existing regions of the viewport, for a live
+    /// component which is independent of the others (e.g. one progress bar per download).
+    ///
+    /// Inline viewports grow while their regions need more rows than their configured height.
+    /// Draw into a region using the area from [Frame::region]; only the regions which change are
+    /// redrawn.
+    pub fn add_region(&mut self, height: u16) -> Result<RegionId, B::Error> {
+        let id = self.regions.add(height);
+        self.fit_regions()?;
+        Ok(id)
+    }
+
+    /// Leave the last drawn content of a region behind above the viewport and remove the region.
+    /// See [insert_before](Self::insert_before).
+    pub fn finish_region(&mut self, id: RegionId) -> Result<(), B::Error> {
+        let Some(area) = self.regions.area(id, self.viewport_area) else {
+            return Ok(());
+        };
+
+        // The previous buffer holds what is on screen after the last draw
+        let screen = &self.buffers[1 - self.current];
+        let width = usize::from(screen.area.width);
+        let start = usize::from(area.top() - screen.area.top()) * width;
+        let rows = screen.content[start..start + usize::from(area.height) * width].to_vec();
+
+        self.insert_before(area.height, |buffer| buffer.content = rows)?;
+        self.remove_region(id)
+    }
+
+    /// Remove a region without leaving its content behind. The regions below it move up.
+    pub fn remove_region(&mut self, id: RegionId) -> Result<(), B::Error> {
+        if self.regions.remove(id) {
+            self.fit_regions()?;
+        }
+        Ok(())
+    }
+
+    /// Change the height of an inline viewport, keeping its top row in place unless the terminal
+    /// has to scroll to make room below it.
+    fn resize_viewport(&mut self, height: u16) -> Result<(), B::Error> {
+        let screen_height = self.last_known_area.height;
+        let height = height.min(screen_height);
+        let previous = self.viewport_area;
+
+        let overflow = (previous.top() + height).saturating_sub(screen_height);
+        self.scroll_up(overflow)?;
+        self.set_viewport_area(Rect {
+            y: previous.top() - overflow,
+            height,
+            ..previous
+        });
+
+        if overflow > 0 {
+            // What was drawn moved up with the scroll, so redraw it all
+            self.clear()?;
+        } else if height != previous.height {
+            // Rows given up by the viewport are cleared, and rows it takes over must start out
+            // blank to match the previous buffer
+            self.backend.set_cursor_position(Position {
+                x: 0,
+                y: previous.top() + height.min(previous.height),
+            })?;
+            self.backend.clear_region(ClearType::AfterCursor)?;
+        }
+        Ok(())
+    }
+
+    /// React to an event read from the backend, resizing the viewport if the terminal was
+    /// resized. Sizes are also checked on every [draw](Self::draw), but handling events redraws
+    /// sooner.
+    ///
+    /// If the terminal was built with
+    /// [suspend_on_ctrl_z](crate::terminal::TerminalBuilder::suspend_on_ctrl_z), CTRL+Z restores
+    /// the terminal and stops the process like it would without raw mode. Once the process is
+    /// continued, the viewport is anchored at the cursor again and fully redrawn by the next draw.
+    pub fn handle_event(&mut self, event: &B::Event) -> Result<(), B::Error> {
+        if self.suspend && self.backend.suspend_event(event) {
+            return self.suspend();
+        }
+        if let Some(size) = self.backend.resize_event(event) {
+            let area: Rect = size.into();
+            if area != self.last_known_area {
+                self.resize(area)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn autoresize(&mut self) -> Result<(), B::Error> {
-        if matches!(self.viewport, Viewport::Inline(_)) {
+        if !matches!(self.viewport, Viewport::Fixed(_)) {
             let area = self.size()?.into();
             if area != self.last_known_area {
                 self.resize(area)?;
@@ -211,12 +1180,11 @@ impl<B: Backend> Terminal<B> {
 
     pub fn clear(&mut self) -> Result<(), B::Error> {
         match self.viewport {
-            Viewport::Inline(_) => {
-                self.backend
-                    .set_cursor_position(self.viewport_area.as_position())?;
+            Viewport::Inline(_) | Viewport::Fit => {
+                self.set_cursor_position(self.viewport_area.as_position())?;
                 self.backend.clear_region(ClearType::AfterCursor)?;
             }
-            Viewport::Fixed(_) => {
+            Viewport::Fixed(_) | Viewport::Footer(_) => {
                 let area = self.viewport_area;
                 for y in area.top()..area.bottom() {
                     self.backend.set_cursor_position(Position { x: 0, y })?;
@@ -225,6 +1193,11 @@ impl<B: Backend> Terminal<B> {
             }
         }
 
+        let shown = &self.buffers[1 - self.current];
+        let deletions = image::kitty_deletions(shown, &Buffer::empty(shown.area));
+        if !deletions.is_empty() {
+            self.backend.write_raw(&deletions)?;
+        }
         self.buffers[1 - self.current].reset();
         Ok(())
     }
@@ -238,13 +1211,161 @@ impl<B: Backend> Terminal<B> {
         self.backend.size()
     }
 
+    /// Read an event if one is ready, along with whether it asks the program to exit.
+    ///
+    /// Backends report CTRL+C as an exit request regardless of configuration, as they don't know
+    /// about [respect_exit](crate::terminal::TerminalBuilder::respect_exit). It is only passed
+    /// on here if the terminal respects it, so callers can act on the flag without checking the
+    /// option themselves.
     pub fn poll_event(&self) -> Option<(B::Event, bool)> {
-        self.backend.read_event()
+        self.backend
+            .read_event()
+            .map(|(event, should_exit)| (event, should_exit && self.respect_exit))
+    }
+
+    /// Wait up to `timeout` for an event. See [poll_event](Self::poll_event).
+    pub fn poll_event_timeout(&self, timeout: Duration) -> Option<(B::Event, bool)> {
+        self.backend
+            .read_event_timeout(timeout)
+            .map(|(event, should_exit)| (event, should_exit && self.respect_exit))
     }
 }
 
-/// Compute the size of the inline viewport
+/// The number of rows of a buffer up to and including the last one which isn't blank.
+pub(crate) fn used_height(buffer: &Buffer) -> u16 {
+    let rows = buffer
+        .content
+        .chunks(usize::from(buffer.area.width.max(1)))
+        .rposition(|row| !row.iter().all(is_blank))
+        .map_or(0, |row| row + 1);
+    u16::try_from(rows).unwrap_or(u16::MAX)
+}
 
+/// Whether a cell shows nothing. Cells covered by wide symbols or images aren't blank.
+fn is_blank(cell: &Cell) -> bool {
+    !cell.skip
+        && cell.symbol() == " "
+        && cell.bg == Color::Reset
+        && cell.attributes == Attributes::none()
+}
+
+/// Convert the colors of every cell in the buffer to ones which can be displayed in the given
+/// [ColorMode].
+fn downgrade_colors(buffer: &mut Buffer, mode: ColorMode) {
+    if mode == ColorMode::TrueColor {
+        return;
+    }
+    for cell in &mut buffer.content {
+        cell.fg = cell.fg.downgrade(mode);
+        cell.bg = cell.bg.downgrade(mode);
+    }
+}
+
+impl<B: Backend> Drop for Terminal<B> {
+    fn drop(&mut self) {
+        let _ = self.restore();
+    }
+}
+
+/// Pin a footer of the given height to the bottom of the screen and limit scrolling to the rows
+/// above it, returning its area and the row where output above it continues.
+///
+/// If the output so far reaches into the footer, the screen is scrolled to make room for it.
+fn place_footer<B: Backend>(
+    backend: &mut B,
+    height: u16,
+    size: Size,
+    output_row: u16,
+) -> Result<(Rect, u16), B::Error> {
+    let height = height.min(size.height);
+    let region = size.height - height;
+
+    backend.reset_scroll_region()?;
+    let overflow = output_row.saturating_sub(region);
+    if overflow > 0 {
+        backend.set_cursor_position(Position {
+            x: 0,
+            y: size.height.saturating_sub(1),
+        })?;
+        backend.append_lines(overflow)?;
+    }
+    if region > 0 {
+        backend.set_scroll_region(0..region)?;
+    }
+
+    let footer = Rect {
+        x: 0,
+        y: region,
+        width: size.width,
+        height,
+    };
+    Ok((footer, output_row - overflow))
+}
+
+/// Lays out styled text as rows of cells no wider than a given width, wrapping long lines.
+struct Wrapper {
+    width: usize,
+    rows: Vec<Vec<Cell>>,
+    row: Vec<Cell>,
+}
+
+impl Wrapper {
+    fn new(width: u16) -> Self {
+        Self {
+            width: usize::from(width.max(1)),
+            rows: vec![],
+            row: vec![],
+        }
+    }
+
+    /// Start the first row at the given column, leaving the cells before it untouched.
+    fn skip(&mut self, columns: u16) {
+        let mut cell = Cell::EMPTY;
+        cell.skip = true;
+        self.row.resize(usize::from(columns).min(self.width), cell);
+    }
+
+    /// Add text to the current row, starting new rows at newlines and when the row is full.
+    fn push<D: Display>(&mut self, styled: &StyledString<D>) {
+        let content = styled.content().to_string();
+        for (i, line) in content.split('\n').enumerate() {
+            if i > 0 {
+                self.newline();
+            }
+
+            for grapheme in line.trim_end_matches('\r').graphemes(true) {
+                let grapheme_width = grapheme.width();
+                if grapheme_width == 0 {
+                    continue;
+                }
+                if self.row.len() + grapheme_width > self.width && !self.row.is_empty() {
+                    self.newline();
+                }
+
+                let mut cell = Cell::EMPTY;
+                cell.set_symbol(grapheme).set_style(*styled.style());
+                self.row.push(cell);
+                for _ in 1..grapheme_width {
+                    let mut cell = Cell::EMPTY;
+                    cell.skip = true;
+                    self.row.push(cell);
+                }
+            }
+        }
+    }
+
+    fn newline(&mut self) {
+        self.rows.push(std::mem::take(&mut self.row));
+    }
+
+    /// The finished rows, and the cells of the last row if it wasn't ended with a newline.
+    fn finish(self) -> (Vec<Vec<Cell>>, Vec<Cell>) {
+        (self.rows, self.row)
+    }
+}
+
+/// Compute the size of the inline viewport
+//
 // This function comes from Ratatui, but isn't explained very well there. This version is highly
 // commented to make it more comprehensible
 fn compute_inline_size<B: Backend>(
@@ -283,3 +1404,532 @@ fn compute_inline_size<B: Backend>(
         pos,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{Options, Terminal};
+    use crate::{
+        backend::{Backend, TestBackend},
+        buffer::{Buffer, Cell},
+        component::{Component, copy::CopyHint},
+        layout::{Position, Rect},
+        style::Stylize,
+        terminal::{Cleanup, Viewport},
+    };
+
+    fn terminal(width: u16, height: u16, viewport_height: u16) -> Terminal<TestBackend> {
+        with_viewport(width, height, Viewport::Inline(viewport_height))
+    }
+
+    fn with_viewport(width: u16, height: u16, viewport: Viewport) -> Terminal<TestBackend> {
+        Terminal::with_options(
+            TestBackend::new(width, height),
+            Options {
+                viewport,
+                ..Options::default()
+            },
+        )
+        .unwrap()
+    }
+
+    fn draw_text(terminal: &mut Terminal<TestBackend>, text: &str) {
+        terminal
+            .draw(|frame| {
+                let area = frame.area();
+                for (y, line) in (area.top()..area.bottom()).zip(text.lines()) {
+                    for (x, c) in (area.left()..area.right()).zip(line.chars()) {
+                        frame.buffer[(x, y)].set_symbol(&c.to_string());
+                    }
+                }
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn insert_lines_above_viewport() {
+        let mut terminal = terminal(10, 5, 1);
+        draw_text(&mut terminal, "live");
+
+        terminal
+            .insert_lines(["one".stylize(), "two".stylize()])
+            .unwrap();
+        draw_text(&mut terminal, "live");
+
+        assert_eq!(
+            terminal.backend().screen_lines(),
+            ["one", "two", "live", "", ""]
+        );
+    }
+
+    #[test]
+    fn insert_lines_scrolls_into_scrollback() {
+        let mut terminal = terminal(10, 4, 2);
+        draw_text(&mut terminal, "live");
+
+        terminal
+            .insert_lines(["1\n2\n3".stylize(), "4".stylize(), "5".stylize()])
+            .unwrap();
+        draw_text(&mut terminal, "live");
+
+        assert_eq!(terminal.backend().scrollback_lines(), ["1", "2", "3"]);
+        assert_eq!(terminal.backend().screen_lines(), ["4", "5", "live", ""]);
+    }
+
+    #[test]
+    fn insert_lines_wraps_long_lines() {
+        let mut terminal = terminal(4, 5, 1);
+
+        terminal.insert_lines(["abcdef".stylize()]).unwrap();
+        draw_text(&mut terminal, "live");
+
+        assert_eq!(
+            terminal.backend().screen_lines(),
+            ["abcd", "ef", "live", "", ""]
+        );
+    }
+
+    #[test]
+    fn print_continues_unfinished_line() {
+        let mut terminal = terminal(10, 5, 1);
+        draw_text(&mut terminal, "live");
+
+        terminal.print("a".stylize()).unwrap();
+        terminal.print("b\nc".stylize()).unwrap();
+        terminal.println("d".stylize()).unwrap();
+        terminal.println("e".stylize()).unwrap();
+        draw_text(&mut terminal, "live");
+
+        assert_eq!(
+            terminal.backend().screen_lines(),
+            ["ab", "cd", "e", "live", ""]
+        );
+    }
+
+    #[test]
+    fn print_styled_wraps_and_scrolls() {
+        let mut terminal = terminal(4, 3, 1);
+
+        terminal
+            .print_styled(["ab".stylize(), "cdef\n".stylize(), "gh".stylize()])
+            .unwrap();
+        draw_text(&mut terminal, "live");
+
+        assert_eq!(terminal.backend().scrollback_lines(), ["abcd"]);
+        assert_eq!(terminal.backend().screen_lines(), ["ef", "gh", "live"]);
+    }
+
+    #[test]
+    fn commit_keeps_frame_and_starts_new_viewport() {
+        let mut terminal = terminal(10, 3, 2);
+        draw_text(&mut terminal, "step 1");
+
+        terminal.commit().unwrap();
+        draw_text(&mut terminal, "step 2");
+        terminal.commit().unwrap();
+        draw_text(&mut terminal, "step 3");
+
+        assert_eq!(terminal.backend().scrollback_lines(), ["step 1"]);
+        assert_eq!(terminal.backend().screen_lines(), ["step 2", "step 3", ""]);
+    }
+
+    #[test]
+    fn fit_viewport_grows_and_shrinks() {
+        let mut terminal = with_viewport(10, 4, Viewport::Fit);
+        terminal.print("above".stylize()).unwrap();
+
+        draw_text(&mut terminal, "a\nb\nc");
+        assert_eq!(terminal.backend().screen_lines(), ["above", "a", "b", "c"]);
+
+        draw_text(&mut terminal, "d");
+        assert_eq!(terminal.backend().screen_lines(), ["above", "d", "", ""]);
+    }
+
+    #[test]
+    fn fit_viewport_scrolls_to_make_room() {
+        let mut terminal = with_viewport(10, 3, Viewport::Fit);
+        terminal.println("1".stylize()).unwrap();
+        terminal.println("2".stylize()).unwrap();
+
+        draw_text(&mut terminal, "a\nb");
+
+        assert_eq!(terminal.backend().scrollback_lines(), ["1"]);
+        assert_eq!(terminal.backend().screen_lines(), ["2", "a", "b"]);
+    }
+
+    #[test]
+    fn fit_viewport_uses_reported_height() {
+        let mut terminal = with_viewport(10, 4, Viewport::Fit);
+
+        terminal
+            .draw(|frame| {
+                let area = frame.area();
+                frame.render_component(CopyHint::new(), area);
+                let below = Rect {
+                    y: area.y + 2,
+                    ..area
+                };
+                frame.render_component(CopyHint::new(), below);
+            })
+            .unwrap();
+        assert_eq!(terminal.get_frame().area().height, 3);
+
+        terminal
+            .draw(|frame| {
+                frame.buffer[(0, 0)].set_symbol("a");
+                frame.buffer[(0, 3)].set_symbol("b");
+                frame.set_content_height(2);
+            })
+            .unwrap();
+        assert_eq!(terminal.get_frame().area().height, 2);
+    }
+
+    #[test]
+    fn set_viewport_height() {
+        let mut terminal = terminal(10, 4, 1);
+        terminal.println("1".stylize()).unwrap();
+        terminal.println("2".stylize()).unwrap();
+        draw_text(&mut terminal, "a");
+
+        terminal.set_viewport_height(3).unwrap();
+        draw_text(&mut terminal, "a\nb\nc");
+        assert_eq!(terminal.backend().scrollback_lines(), ["1"]);
+        assert_eq!(terminal.backend().screen_lines(), ["2", "a", "b", "c"]);
+
+        terminal.set_viewport_height(1).unwrap();
+        draw_text(&mut terminal, "d");
+        assert_eq!(terminal.backend().scrollback_lines(), ["1"]);
+        assert_eq!(terminal.backend().screen_lines(), ["2", "d", "", ""]);
+    }
+
+    #[test]
+    fn finished_regions_move_above_viewport() {
+        let mut terminal = terminal(10, 5, 1);
+        let first = terminal.add_region(1).unwrap();
+        let second = terminal.add_region(2).unwrap();
+
+        let draw = |terminal: &mut Terminal<TestBackend>, first_text: &str| {
+            terminal
+                .draw(|frame| {
+                    if let Some(area) = frame.region(first) {
+                        for (x, c) in (area.left()..area.right()).zip(first_text.chars()) {
+                            frame.buffer[(x, area.y)].set_symbol(&c.to_string());
+                        }
+                    }
+                    let area = frame.region(second).unwrap();
+                    frame.buffer[(0, area.y)].set_symbol("b");
+                    frame.buffer[(0, area.y + 1)].set_symbol("c");
+                })
+                .unwrap();
+        };
+
+        draw(&mut terminal, "a");
+        assert_eq!(terminal.backend().screen_lines(), ["a", "b", "c", "", ""]);
+
+        draw(&mut terminal, "done");
+        terminal.finish_region(first).unwrap();
+        draw(&mut terminal, "");
+        assert_eq!(
+            terminal.backend().screen_lines(),
+            ["done", "b", "c", "", ""]
+        );
+
+        terminal.remove_region(second).unwrap();
+        draw_text(&mut terminal, "");
+        assert_eq!(terminal.backend().screen_lines(), ["done", "", "", "", ""]);
+    }
+
+    #[test]
+    fn regions_keep_configured_height() {
+        let mut terminal = terminal(10, 8, 3);
+        let small = terminal.add_region(1).unwrap();
+        assert_eq!(terminal.get_frame().area().height, 3);
+        terminal.remove_region(small).unwrap();
+        assert_eq!(terminal.get_frame().area().height, 3);
+
+        let large = terminal.add_region(5).unwrap();
+        assert_eq!(terminal.get_frame().area().height, 5);
+        terminal.remove_region(large).unwrap();
+        assert_eq!(terminal.get_frame().area().height, 3);
+    }
+
+    #[test]
+    fn render_once_measures_component() {
+        struct Banner;
+
+        impl Component for Banner {
+            fn render(self, area: Rect, buffer: &mut Buffer) {
+                buffer[(area.x, area.y)].set_symbol("+");
+                buffer[(area.x, area.y + 1)].set_symbol("|");
+                buffer[(area.x, area.y + 2)].set_symbol("+");
+            }
+        }
+
+        let mut terminal = terminal(20, 5, 1);
+        draw_text(&mut terminal, "live");
+
+        terminal.render_once(Banner).unwrap();
+        terminal.render_once(CopyHint::new()).unwrap();
+        draw_text(&mut terminal, "live");
+
+        assert_eq!(
+            terminal.backend().screen_lines(),
+            ["+", "|", "+", "press y to copy", "live"]
+        );
+    }
+
+    #[test]
+    fn lazy_raw_mode_only_while_interactive() {
+        let mut terminal = Terminal::with_options(
+            TestBackend::new(10, 5),
+            Options {
+                lazy_raw_mode: true,
+                ..Options::default()
+            },
+        )
+        .unwrap();
+        assert!(!terminal.backend().raw_mode());
+
+        terminal.enter_interactive().unwrap();
+        assert!(terminal.backend().raw_mode());
+        draw_text(&mut terminal, "first");
+        terminal.leave_interactive().unwrap();
+        assert!(!terminal.backend().raw_mode());
+        assert_eq!(terminal.backend().cursor(), Position { x: 0, y: 1 });
+
+        // Normal output moves the cursor while the terminal isn't interactive
+        terminal.backend_mut().append_lines(1).unwrap();
+
+        terminal.enter_interactive().unwrap();
+        draw_text(&mut terminal, "second");
+        terminal.leave_interactive().unwrap();
+        assert_eq!(
+            terminal.backend().screen_lines(),
+            ["first", "", "second", "", ""]
+        );
+    }
+
+    #[test]
+    fn exit_only_reported_when_respected() {
+        let ctrl_c = Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        for respect_exit in [true, false] {
+            let mut backend = TestBackend::new(10, 3);
+            backend.push_event(ctrl_c.clone());
+            backend.push_event(ctrl_c.clone());
+            let terminal = Terminal::with_options(
+                backend,
+                Options {
+                    respect_exit,
+                    ..Options::default()
+                },
+            )
+            .unwrap();
+
+            assert_eq!(terminal.poll_event(), Some((ctrl_c.clone(), respect_exit)));
+            assert_eq!(
+                terminal.poll_event_timeout(Duration::ZERO),
+                Some((ctrl_c.clone(), respect_exit))
+            );
+        }
+    }
+
+    #[test]
+    fn lazy_output_between_sections_is_kept() {
+        let mut terminal = Terminal::with_options(
+            TestBackend::new(20, 8),
+            Options {
+                lazy_raw_mode: true,
+                ..Options::default()
+            },
+        )
+        .unwrap();
+        let println = |terminal: &mut Terminal<TestBackend>, text: &str| {
+            let backend = terminal.backend_mut();
+            let Position { x, y } = backend.cursor();
+            let mut cells = vec![Cell::EMPTY; text.len()];
+            for (cell, c) in cells.iter_mut().zip(text.chars()) {
+                cell.set_symbol(&c.to_string());
+            }
+            let content = (x..).zip(&cells).map(|(x, cell)| (x, y, cell));
+            backend.draw(content).unwrap();
+            backend.append_lines(1).unwrap();
+        };
+
+        terminal.enter_interactive().unwrap();
+        draw_text(&mut terminal, "first");
+        terminal.leave_interactive().unwrap();
+
+        println(&mut terminal, "one");
+        terminal.render_once(CopyHint::new()).unwrap();
+        println(&mut terminal, "two");
+        terminal.insert_lines(["three".stylize()]).unwrap();
+        println(&mut terminal, "four");
+        draw_text(&mut terminal, "last");
+        assert!(!terminal.backend().raw_mode());
+        assert_eq!(
+            terminal.backend().screen_lines(),
+            [
+                "first",
+                "one",
+                "press y to copy",
+                "two",
+                "three",
+                "four",
+                "last",
+                ""
+            ]
+        );
+    }
+
+    #[test]
+    fn resize_reflows_inline_viewport() {
+        let mut terminal = terminal(10, 6, 2);
+        terminal
+            .insert_lines(["0123456789".stylize(), "ab".stylize()])
+            .unwrap();
+        draw_text(&mut terminal, "live-12345\nx");
+
+        // Narrowing wraps the full-width lines, including the viewport's first row
+        terminal.backend_mut().resize(5, 6);
+        draw_text(&mut terminal, "live\nx");
+        assert_eq!(
+            terminal.backend().screen_lines(),
+            ["01234", "56789", "ab", "live", "x", ""]
+        );
+
+        // Widening unwraps them again
+        terminal.backend_mut().resize(10, 6);
+        let resize = Event::Resize(10, 6);
+        terminal.handle_event(&resize).unwrap();
+        draw_text(&mut terminal, "live-12345\nx");
+        assert_eq!(
+            terminal.backend().screen_lines(),
+            ["0123456789", "ab", "live-12345", "x", "", ""]
+        );
+        assert!(terminal.backend().scrollback_lines().is_empty());
+    }
+
+    #[test]
+    fn footer_stays_while_output_scrolls_above() {
+        let mut terminal = with_viewport(10, 5, Viewport::Footer(1));
+        draw_text(&mut terminal, "status");
+
+        for line in ["a", "b", "c", "d", "e", "f"] {
+            terminal.println(line.stylize()).unwrap();
+        }
+        draw_text(&mut terminal, "status");
+
+        assert_eq!(terminal.backend().scrollback_lines(), ["a", "b"]);
+        assert_eq!(
+            terminal.backend().screen_lines(),
+            ["c", "d", "e", "f", "status"]
+        );
+    }
+
+    #[test]
+    fn cleanup_leaves_cursor_below_viewport() {
+        let mut kept = terminal(10, 5, 3);
+        kept.backend_mut().enable_raw_mode().unwrap();
+        draw_text(&mut kept, "one\ntwo");
+        kept.restore().unwrap();
+        assert_eq!(kept.backend().screen_lines()[..2], ["one", "two"]);
+        assert_eq!(kept.backend().cursor(), Position { x: 0, y: 2 });
+        assert!(kept.backend().cursor_visible());
+
+        for (cleanup, expected) in [
+            (Cleanup::Clear, ["", ""]),
+            (Cleanup::Summary("done".into()), ["done", ""]),
+            (Cleanup::Summary("finished all".into()), ["finished a", ""]),
+        ] {
+            let mut terminal = terminal(10, 5, 3);
+            terminal.backend_mut().enable_raw_mode().unwrap();
+            terminal.cleanup = cleanup;
+            draw_text(&mut terminal, "one\ntwo");
+            terminal.restore().unwrap();
+            assert_eq!(terminal.backend().screen_lines()[..2], expected);
+            let row = u16::from(!expected[0].is_empty());
+            assert_eq!(terminal.backend().cursor(), Position { x: 0, y: row });
+        }
+    }
+
+    #[test]
+    fn cleanup_skipped_after_raw_mode_left() {
+        let mut terminal = terminal(10, 5, 3);
+        terminal.cleanup = Cleanup::Clear;
+        terminal.backend_mut().enable_raw_mode().unwrap();
+        draw_text(&mut terminal, "one\ntwo");
+
+        // As if `tdrop::restore` was called before the terminal was dropped
+        terminal.backend_mut().disable_raw_mode().unwrap();
+        terminal.restore().unwrap();
+        assert_eq!(terminal.backend().screen_lines()[..2], ["one", "two"]);
+        assert!(terminal.backend().cursor_visible());
+    }
+
+    #[test]
+    fn draw_reports_changed_cells() {
+        let mut terminal = terminal(10, 5, 2);
+        draw_text(&mut terminal, "ab\ncd");
+        assert_eq!(terminal.stats().changed_cells, 4);
+        assert_eq!(terminal.stats().frame_time, None);
+
+        let stats = terminal
+            .draw(|frame| {
+                frame.buffer[(0, 0)].set_symbol("a");
+                frame.buffer[(1, 0)].set_symbol("x");
+            })
+            .unwrap()
+            .stats;
+        // "x" replaces "b" and the second line is cleared
+        assert_eq!(stats.changed_cells, 3);
+        assert!(stats.frame_time.is_some());
+    }
+
+    #[test]
+    fn ctrl_z_suspends_and_redraws() {
+        let mut terminal = Terminal::with_options(
+            TestBackend::new(10, 5),
+            Options {
+                viewport: Viewport::Inline(2),
+                suspend: true,
+                ..Options::default()
+            },
+        )
+        .unwrap();
+        draw_text(&mut terminal, "ab\ncd");
+        draw_text(&mut terminal, "ab\ncd");
+        assert_eq!(terminal.stats().changed_cells, 0);
+
+        let ctrl_z = Event::Key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL));
+        terminal.handle_event(&ctrl_z).unwrap();
+        assert!(terminal.backend().raw_mode());
+        assert!(terminal.backend().cursor_visible());
+
+        // The viewport moved below the last frame, where the shell would continue
+        assert_eq!(terminal.get_frame().area().top(), 2);
+        draw_text(&mut terminal, "ab\ncd");
+        assert_eq!(terminal.stats().changed_cells, 4);
+        assert_eq!(
+            terminal.backend().screen_lines(),
+            ["ab", "cd", "ab", "cd", ""]
+        );
+    }
+
+    #[test]
+    fn suspend_while_hands_over_terminal() {
+        let mut terminal = terminal(10, 5, 2);
+        draw_text(&mut terminal, "ab\ncd");
+        assert_eq!(terminal.suspend_while(|| 42).unwrap(), 42);
+        assert!(terminal.backend().raw_mode());
+        assert_eq!(terminal.get_frame().area().top(), 2);
+
+        draw_text(&mut terminal, "ab");
+        assert_eq!(
+            terminal.backend().screen_lines(),
+            ["ab", "cd", "ab", "", ""]
+        );
+    }
+}
@@ -4,4 +4,28 @@ use crate::layout::Rect;
 pub enum Viewport {
     Inline(u16),
     Fixed(Rect),
+    /// An inline viewport which grows and shrinks to fit what is drawn in it each frame.
+    ///
+    /// The height is taken from components which report it through
+    /// [Component::height](crate::component::Component::height), or otherwise from the last row
+    /// which was drawn to. It is never taller than the screen.
+    Fit,
+    /// A viewport of the given height pinned to the bottom of the screen, like a status bar.
+    ///
+    /// The rows above it are made a scroll region, so output printed with
+    /// [Terminal::print](crate::terminal::Terminal::print) and friends scrolls up above the
+    /// footer while the footer stays in place.
+    Footer(u16),
+}
+
+impl Viewport {
+    /// Whether the viewport is part of the normal terminal output rather than a fixed area.
+    pub(crate) const fn is_inline(&self) -> bool {
+        matches!(self, Self::Inline(_) | Self::Fit)
+    }
+
+    /// Whether output can be printed above the viewport.
+    pub(crate) const fn has_output_above(&self) -> bool {
+        !matches!(self, Self::Fixed(_))
+    }
 }